@@ -0,0 +1,163 @@
+use std::borrow::Cow;
+
+use arboard::ImageData;
+use eframe::egui::Ui;
+use image::{DynamicImage, RgbaImage};
+
+use crate::{
+    editor::Editor,
+    image::Image,
+    modifier::{
+        cation::{Cation, DynMod},
+        save::Node,
+    },
+    slot::ModifierSlot,
+};
+
+/// The system clipboard, opened the first time it is used and kept open after that.
+#[derive(Default)]
+pub struct Clipboard {
+    clipboard: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    fn open(&mut self) -> Result<&mut arboard::Clipboard, String> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(arboard::Clipboard::new().map_err(|err| err.to_string())?);
+        }
+        Ok(self.clipboard.as_mut().unwrap())
+    }
+
+    /// Puts a modifier with everything nested in it on the clipboard, as the json also used in
+    /// project files.
+    pub fn copy(&mut self, cation: &Cation<DynMod>) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&Node::from_cation(cation))
+            .map_err(|err| err.to_string())?;
+        self.open()?.set_text(json).map_err(|err| err.to_string())
+    }
+
+    fn paste_node(&mut self) -> Result<Node, String> {
+        let json = self.open()?.get_text().map_err(|err| err.to_string())?;
+        serde_json::from_str(&json).map_err(|err| err.to_string())
+    }
+
+    /// Puts an image on the clipboard.
+    pub fn copy_image(&mut self, image: &Image) -> Result<(), String> {
+        let (width, height) = image.as_rgba8().dimensions();
+        let data = ImageData {
+            width: width as usize,
+            height: height as usize,
+            bytes: Cow::Borrowed(image.as_rgba8().as_raw()),
+        };
+
+        self.open()?.set_image(data).map_err(|err| err.to_string())
+    }
+
+    /// Reads an image from the clipboard.
+    pub fn paste_image(&mut self) -> Result<Image, String> {
+        let data = self.open()?.get_image().map_err(|err| err.to_string())?;
+
+        RgbaImage::from_raw(
+            data.width as u32,
+            data.height as u32,
+            data.bytes.into_owned(),
+        )
+        .map(|image| Image::from_dyn(DynamicImage::ImageRgba8(image)))
+        .ok_or_else(|| "clipboard image has the wrong size".to_string())
+    }
+}
+
+/// Reads a modifier from the system clipboard, with new ids so it can live next to the original.
+pub fn paste(editor: &mut Editor) -> Result<Cation<DynMod>, String> {
+    let node = editor.clipboard.paste_node()?;
+
+    let index = editor
+        .index
+        .iter()
+        .chain(&editor.macros)
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut cation = node.into_cation_with(&index)?;
+    cation.renew_ids();
+    Ok(cation)
+}
+
+/// Copy, cut, duplicate and paste for a modifier in the panel. Only a list or graph can hold a
+/// copy next to the modifier, elsewhere pasting replaces it.
+pub fn menu(slot: &mut ModifierSlot, ui: &mut Ui, editor: &mut Editor, in_sequence: bool) {
+    let Some(cation) = slot.mod_ref() else {
+        return;
+    };
+
+    let mut cut = false;
+    let mut replacement = None;
+
+    ui.menu_button("📋", |ui| {
+        if ui.button("copy").clicked() {
+            if let Err(err) = editor.clipboard.copy(cation) {
+                editor
+                    .notifications
+                    .error(format!("failed to copy modifier: {err}"));
+            }
+            ui.close_menu();
+        }
+
+        if ui.button("cut").clicked() {
+            match editor.clipboard.copy(cation) {
+                Ok(()) => cut = true,
+                Err(err) => editor
+                    .notifications
+                    .error(format!("failed to cut modifier: {err}")),
+            }
+            ui.close_menu();
+        }
+
+        if in_sequence && ui.button("duplicate").clicked() {
+            let mut copy = cation.clone();
+            copy.renew_ids();
+            editor.insert = Some((cation.id, copy));
+            ui.close_menu();
+        }
+
+        let paste_label = if in_sequence { "paste after" } else { "paste" };
+        if ui.button(paste_label).clicked() {
+            match paste(editor) {
+                Ok(pasted) if in_sequence => editor.insert = Some((cation.id, pasted)),
+                Ok(pasted) => replacement = Some(pasted),
+                Err(err) => editor
+                    .notifications
+                    .error(format!("failed to paste modifier: {err}")),
+            }
+            ui.close_menu();
+        }
+    });
+
+    if cut {
+        *slot = ModifierSlot::Empty;
+    }
+
+    if let Some(pasted) = replacement {
+        editor.select_cation(&pasted);
+        *slot = ModifierSlot::from_cacher(pasted);
+    }
+}
+
+/// Button that pastes a modifier from the clipboard.
+pub fn paste_button(ui: &mut Ui, editor: &mut Editor) -> Option<ModifierSlot> {
+    if !ui.button("📋").on_hover_text("paste").clicked() {
+        return None;
+    }
+
+    match paste(editor) {
+        Ok(pasted) => {
+            editor.select_cation(&pasted);
+            Some(ModifierSlot::from_cacher(pasted))
+        }
+        Err(err) => {
+            editor
+                .notifications
+                .error(format!("failed to paste modifier: {err}"));
+            None
+        }
+    }
+}
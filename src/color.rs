@@ -1,6 +1,8 @@
+#[cfg(feature = "gui")]
 use eframe::egui::Color32;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Color {
     pub r: f32,
     pub g: f32,
@@ -9,11 +11,14 @@ pub struct Color {
 }
 
 impl Color {
+    pub const BLACK: Self = Self::from_rgba(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: Self = Self::from_rgba(1.0, 1.0, 1.0, 1.0);
+
     pub fn sum_rgb(self) -> f32 {
         self.r + self.g + self.b
     }
 
-    pub fn from_rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+    pub const fn from_rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
         Self { r, g, b, a }
     }
 
@@ -52,12 +57,92 @@ impl Color {
 
         Self::from_rgba(r + m, g + m, b + m, 1.0)
     }
+
+    pub fn into_hsv(self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+
+        let h = if delta == 0.0 {
+            0.0
+        } else if max == self.r {
+            60.0 * ((self.g - self.b) / delta).rem_euclid(6.0)
+        } else if max == self.g {
+            60.0 * ((self.b - self.r) / delta + 2.0)
+        } else {
+            60.0 * ((self.r - self.g) / delta + 4.0)
+        };
+
+        let s = if max == 0.0 { 0.0 } else { delta / max };
+
+        (h.to_radians(), s, max)
+    }
+
+    pub fn to_hex(self) -> String {
+        let [r, g, b, a] = self.into_rgba_u8();
+        format!("#{r:02X}{g:02X}{b:02X}{a:02X}")
+    }
+
+    /// Parses `#RRGGBB` or `#RRGGBBAA`, the leading `#` being optional.
+    pub fn from_hex(text: &str) -> Option<Self> {
+        let text = text.strip_prefix('#').unwrap_or(text);
+        if !matches!(text.len(), 6 | 8) || !text.is_ascii() {
+            return None;
+        }
+
+        let channel = |index: usize| u8::from_str_radix(text.get(index..index + 2)?, 16).ok();
+        let alpha = if text.len() == 8 {
+            channel(6)?
+        } else {
+            u8::MAX
+        };
+
+        Some(Self::from_rgba_u8(
+            channel(0)?,
+            channel(2)?,
+            channel(4)?,
+            alpha,
+        ))
+    }
 }
 
+#[cfg(feature = "gui")]
 impl From<Color32> for Color {
     fn from(color: Color32) -> Self {
-        let (r, g, b, a) = color.to_tuple();
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
 
         Self::from_rgba_u8(r, g, b, a)
     }
 }
+
+#[cfg(feature = "gui")]
+impl From<Color> for Color32 {
+    fn from(color: Color) -> Self {
+        let [r, g, b, a] = color.into_rgba_u8();
+
+        Self::from_rgba_unmultiplied(r, g, b, a)
+    }
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color32_round_trip() {
+        for rgba in [
+            [255, 0, 0, 255],
+            [12, 34, 56, 255],
+            [200, 100, 50, 128],
+            [0, 0, 0, 0],
+        ] {
+            let color = Color::from_rgba_u8(rgba[0], rgba[1], rgba[2], rgba[3]);
+            let back = Color::from(Color32::from(color)).into_rgba_u8();
+
+            for (channel, expected) in back.into_iter().zip(rgba) {
+                // premultiplying loses some precision at partial alpha
+                assert!(channel.abs_diff(expected) <= 1, "{rgba:?} became {back:?}");
+            }
+        }
+    }
+}
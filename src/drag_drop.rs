@@ -0,0 +1,157 @@
+use eframe::egui::{self, Align2, Color32, Context, DroppedFile, FontId, Id, LayerId, Order, Rect};
+use image::ImageFormat;
+
+use crate::{
+    editor::Editor,
+    image::Image,
+    modifier::{
+        cation::{Cation, DynMod},
+        collection::{overlay::Overlay, source::Source},
+    },
+    notifications::Notifications,
+    project::Project,
+    slot::ModifierSlot,
+};
+
+/// Opens files dropped on the canvas like one picked with "new", and adds images dropped on the
+/// modifier panel as sources, or as overlays of sources when the project already has modifiers.
+pub fn handle(ctx: &Context, editor: &mut Editor, project: &mut Project) {
+    let (hovering, dropped, pointer) = ctx.input(|input| {
+        (
+            !input.raw.hovered_files.is_empty(),
+            input.raw.dropped_files.clone(),
+            input.pointer.latest_pos(),
+        )
+    });
+
+    let screen = ctx.screen_rect();
+    let panel = editor.panel.unwrap_or(Rect::NOTHING);
+    let on_panel = pointer.is_some_and(|pointer| panel.contains(pointer));
+    let overlay = !project.root.modifier.contents.is_empty();
+
+    if hovering {
+        let (rect, text) = if on_panel {
+            (
+                panel,
+                if overlay {
+                    "drop to overlay"
+                } else {
+                    "drop to add"
+                },
+            )
+        } else {
+            let canvas = Rect::from_min_max(
+                egui::pos2(panel.max.x.max(screen.min.x), screen.min.y),
+                screen.max,
+            );
+            (canvas, "drop to open")
+        };
+
+        let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("drop files")));
+        painter.rect_filled(rect, 0.0, Color32::from_black_alpha(160));
+        painter.text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            text,
+            FontId::proportional(24.0),
+            Color32::WHITE,
+        );
+    }
+
+    if dropped.is_empty() {
+        return;
+    }
+
+    if on_panel {
+        add(editor, project, &dropped, overlay);
+    } else if let Some(opened) = open(editor, &dropped) {
+        editor.confirm_replace_project(project, opened);
+    }
+}
+
+/// A new project from the first file, with the others drawn on top of it.
+fn open(editor: &mut Editor, dropped: &[DroppedFile]) -> Option<Project> {
+    let (file, rest) = dropped.split_first()?;
+
+    let mut opened = match &file.path {
+        Some(path) => editor
+            .picker
+            .open(path.clone(), &mut editor.notifications)?,
+        None => match source(file) {
+            Ok(source) => Project::new_from_source(source),
+            Err(err) => {
+                editor
+                    .notifications
+                    .error(format!("failed to open dropped file: {err}"));
+                return None;
+            }
+        },
+    };
+
+    let overlays = cations(rest, true, &mut editor.notifications);
+    opened
+        .root
+        .modifier
+        .contents
+        .extend(overlays.into_iter().map(ModifierSlot::from_cacher));
+
+    Some(opened)
+}
+
+/// Adds the files after the selected modifier, or at the end of the project.
+fn add(editor: &mut Editor, project: &mut Project, dropped: &[DroppedFile], overlay: bool) {
+    let mut cations = cations(dropped, overlay, &mut editor.notifications);
+
+    // only one modifier can be inserted after the selected one at a time
+    if cations.len() == 1 {
+        editor.insert_after_selected(cations.remove(0));
+    } else if let Some(last) = cations.last() {
+        editor.select_cation(last);
+        project
+            .root
+            .modifier
+            .contents
+            .extend(cations.into_iter().map(ModifierSlot::from_cacher));
+    }
+}
+
+/// Sources of the images, every one after the first as an overlay, or all of them if `overlay`.
+fn cations(
+    dropped: &[DroppedFile],
+    overlay: bool,
+    notifications: &mut Notifications,
+) -> Vec<Cation<DynMod>> {
+    dropped
+        .iter()
+        .filter_map(|file| {
+            source(file)
+                .map_err(|err| notifications.error(format!("failed to add dropped file: {err}")))
+                .ok()
+        })
+        .enumerate()
+        .map(|(i, source)| {
+            if overlay || i > 0 {
+                let mut modifier = Overlay::default();
+                modifier.input = ModifierSlot::from_mod(source);
+                Cation::new(DynMod::new(modifier))
+            } else {
+                Cation::new(DynMod::new(source))
+            }
+        })
+        .collect()
+}
+
+/// A source of the file on disk, or of its contents when it has no path, like on the web.
+fn source(file: &DroppedFile) -> Result<Source, String> {
+    match (&file.path, &file.bytes) {
+        (Some(path), _) => {
+            ImageFormat::from_path(path).map_err(|err| err.to_string())?;
+            Ok(Source::new(path))
+        }
+        (None, Some(bytes)) => {
+            let image = Image::from_memory(bytes).map_err(|err| err.to_string())?;
+            Source::embedded(image)
+        }
+        (None, None) => Err(format!("no contents for `{}`", file.name)),
+    }
+}
@@ -1,26 +1,90 @@
-use eframe::egui::{self, Context};
+use std::{any::TypeId, collections::HashSet, path::PathBuf, rc::Rc};
+
+use eframe::egui::{self, Context, Rect, Ui, ViewportCommand};
 use uuid::Uuid;
 
 use crate::{
+    clipboard::Clipboard,
     file_picker::FilePicker,
+    history::History,
+    keymap::Keymap,
     modifier::{
         cation::{Cation, DynMod},
+        collection::list::List,
+        label,
+        macros::{self, MacroDef},
         traits::{Modifier, ModifierIndex},
     },
+    notifications::Notifications,
+    palette::{self, Palette},
+    presets::Presets,
+    preview::Preview,
+    profiler::Profiler,
     project::Project,
     slot::ModifierSlot,
+    thumbnails::Thumbnails,
+    timeline::Timeline,
+    usage::Usage,
     view::View,
 };
 
 #[derive(Default)]
 pub struct Editor {
     pub index: Vec<ModifierIndex>,
+    /// The project's macros, offered next to the built-in modifiers.
+    pub macros: Vec<ModifierIndex>,
+    /// A macro saved in the modifier panel, defined in the project after it is drawn.
+    pub new_macro: Option<Rc<MacroDef>>,
     pub selected: Option<ModId>,
+    /// Everything selected with ctrl or shift clicks, including [`Self::selected`].
+    pub selection: HashSet<Uuid>,
+    /// A shift clicked modifier, selected together with those between it and the selected one by
+    /// the list holding both.
+    pub select_range: Option<Uuid>,
+    /// Every modifier in the project with a label, for picking one like the target of a
+    /// reference.
+    pub modifiers: Vec<(Uuid, String)>,
+    /// The modifier outlined in the panel.
+    pub highlighted: Option<Uuid>,
+    /// Set while drawing to outline a modifier in the next frame.
+    pub highlight: Option<Uuid>,
+    /// A copy to insert after the modifier with the id, by the list or graph holding it.
+    pub insert: Option<(Uuid, Cation<DynMod>)>,
     pub dragging: Option<Cation<DynMod>>,
     pub dropped: Option<Cation<DynMod>>,
     pub view: View,
     pub picker: FilePicker,
     pub add_mod_text: String,
+    /// Only modifiers with this in their type, name or note are shown in the panel.
+    pub filter: String,
+    pub thumbnails: Thumbnails,
+    pub profiler: Profiler,
+    pub timeline: Timeline,
+    pub presets: Presets,
+    /// The project's current time, for keyframing.
+    pub time: f32,
+    pub keymap: Keymap,
+    pub clipboard: Clipboard,
+    pub notifications: Notifications,
+    pub history: History,
+    pub palette: Palette,
+    /// Favourite and recently added modifiers, listed first when adding one.
+    pub usage: Usage,
+    pub preview: Preview,
+    /// Whether the export settings are shown before picking where to export to.
+    pub exporting: bool,
+    /// Where the modifier panel was drawn, to tell files dropped on it from those dropped on the
+    /// canvas.
+    pub panel: Option<Rect>,
+    /// What waits for asking whether to save the project first.
+    unsaved: Option<Unsaved>,
+    exit_confirmed: bool,
+}
+
+/// What is done once unsaved changes are discarded.
+enum Unsaved {
+    Exit,
+    Replace(Box<Project>),
 }
 
 pub struct ModId {
@@ -55,7 +119,28 @@ impl Editor {
     }
 
     pub fn view(&mut self, ctx: &Context, project: &mut Project) {
-        egui::SidePanel::left("Modifiers")
+        self.thumbnails.update(ctx);
+        self.macros = project.macros.indices();
+        self.modifiers = project
+            .root
+            .descendants()
+            .into_iter()
+            .enumerate()
+            .map(|(i, cation)| (cation.id, format!("{i}: {}", label::title(cation))))
+            .collect();
+        self.highlighted = self.highlight.take();
+        self.selection.retain(|id| project.find(*id).is_some());
+
+        self.timeline.view(ctx, project);
+        self.time = project.time();
+
+        self.keymap.view(ctx);
+        palette::view(ctx, self, project);
+        self.unsaved_dialog(ctx, project);
+        self.notifications.show(ctx);
+        self.export_dialog(ctx, project);
+
+        let panel = egui::SidePanel::left("Modifiers")
             .resizable(true)
             .show(ctx, |ui| {
                 ui.vertical_centered(|ui| {
@@ -64,9 +149,43 @@ impl Editor {
                         project.root.modifier.contents.len()
                     ));
                 });
+                ui.horizontal(|ui| {
+                    ui.label("🔍");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.filter)
+                            .hint_text("filter by type, name or note"),
+                    );
+                    if !self.filter.is_empty() && ui.small_button("✖").clicked() {
+                        self.filter.clear();
+                    }
+                });
                 ui.separator();
+                if !self.selection.is_empty() {
+                    self.selection_bar(ui, project);
+                }
+                self.preview.parent = Some(project.root.id);
+                self.view.guides = std::mem::take(&mut project.guides);
                 project.root.modifier.view(ui, self);
+                project.guides = std::mem::take(&mut self.view.guides);
+                self.preview.parent = None;
             });
+        self.panel = Some(panel.response.rect);
+
+        // a copy for a slot outside of any list goes at the end
+        if let Some((_, cation)) = self.insert.take() {
+            project
+                .root
+                .modifier
+                .contents
+                .push(ModifierSlot::from_cacher(cation));
+        }
+        self.selection.extend(self.select_range.take());
+
+        if let Some(def) = self.new_macro.take() {
+            project.define_macro(def);
+        }
+
+        self.profiler.view(ctx, project);
 
         if !ctx.memory(|memory| memory.is_anything_being_dragged()) {
             if self.dragging.is_some() {
@@ -74,24 +193,228 @@ impl Editor {
                 ctx.request_repaint();
             }
         }
+
+        project.update_revision();
+
+        // changes are recorded once a drag or text edit is done, not every step of it
+        if !ctx.input(|input| input.pointer.any_down()) && !ctx.wants_keyboard_input() {
+            self.history.record(project);
+        }
+    }
+
+    /// Switches to another project, forgetting the changes that could be undone in this one, and
+    /// fits it in the view.
+    pub fn replace_project(&mut self, project: &mut Project, replacement: Project) {
+        *project = replacement;
+        self.history = History::default();
+        self.view.request_fit();
+        // a reopened file has the same ids and starts counting revisions again
+        self.preview = Preview::default();
+    }
+
+    /// Replaces the project once it is saved or its changes are discarded, if it has any.
+    pub fn confirm_replace_project(&mut self, project: &mut Project, replacement: Project) {
+        if project.is_modified() {
+            self.unsaved = Some(Unsaved::Replace(Box::new(replacement)));
+        } else {
+            self.replace_project(project, replacement);
+        }
+    }
+
+    /// Opens a project file, or starts a new project from an image.
+    pub fn open(&mut self, path: PathBuf, project: &mut Project) {
+        if let Some(opened) = self.picker.open(path, &mut self.notifications) {
+            self.replace_project(project, opened);
+        }
+    }
+
+    /// Adds the modifier after the selected one, or at the end of the project, and selects it.
+    pub fn insert_after_selected(&mut self, cation: Cation<DynMod>) {
+        let anchor = self.selected_id().unwrap_or(Uuid::nil());
+        self.select_cation(&cation);
+        self.insert = Some((anchor, cation));
+    }
+
+    /// Asks to save when closing the window or replacing the project with unsaved changes.
+    fn unsaved_dialog(&mut self, ctx: &Context, project: &mut Project) {
+        if ctx.input(|input| input.viewport().close_requested())
+            && !self.exit_confirmed
+            && project.is_modified()
+        {
+            ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+            self.unsaved = Some(Unsaved::Exit);
+        }
+
+        let discard = match &self.unsaved {
+            Some(Unsaved::Exit) => "exit without saving",
+            Some(Unsaved::Replace(_)) => "discard changes",
+            None => return,
+        };
+
+        // saving goes through the file picker, the dialog stays open until it succeeds
+        let mut discarded = (!project.is_modified()).then_some(true);
+
+        egui::Window::new("unsaved changes")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("The project has changes that are not saved.");
+                ui.add_enabled_ui(!self.picker.is_open(), |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button("save").clicked() {
+                            if let Err(err) = self.picker.menu_save_project() {
+                                self.notifications
+                                    .error(format!("failed to save project: {err}"));
+                            }
+                        }
+                        if ui.button(discard).clicked() {
+                            discarded = Some(true);
+                        }
+                        if ui.button("cancel").clicked() {
+                            discarded = Some(false);
+                        }
+                    });
+                });
+            });
+
+        match (discarded, self.unsaved.take()) {
+            (Some(true), Some(Unsaved::Exit)) => {
+                self.exit_confirmed = true;
+                ctx.send_viewport_cmd(ViewportCommand::Close);
+            }
+            (Some(true), Some(Unsaved::Replace(replacement))) => {
+                self.replace_project(project, *replacement)
+            }
+            (None, unsaved) => self.unsaved = unsaved,
+            _ => (),
+        }
+    }
+
+    /// The export settings, with a button to pick the file to export to.
+    fn export_dialog(&mut self, ctx: &Context, project: &mut Project) {
+        let mut open = self.exporting;
+
+        egui::Window::new("export")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                project.export.view(ui);
+                ui.separator();
+
+                ui.add_enabled_ui(!self.picker.is_open(), |ui| {
+                    if ui.button("export…").clicked() {
+                        self.picker
+                            .menu_export(project.path(), project.export.format)
+                            .ok();
+                        self.exporting = false;
+                    }
+                });
+            });
+
+        self.exporting &= open;
+    }
+
+    /// Actions for every selected modifier at once.
+    fn selection_bar(&mut self, ui: &mut Ui, project: &mut Project) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!("{} selected", self.selection.len()));
+
+            if ui.small_button("⬆").on_hover_text("move up").clicked() {
+                project.move_selected(&self.selection, true);
+            }
+            if ui.small_button("⬇").on_hover_text("move down").clicked() {
+                project.move_selected(&self.selection, false);
+            }
+
+            let bypassed = self
+                .selection
+                .iter()
+                .all(|id| project.find(*id).is_some_and(|cation| cation.bypass));
+            if ui.selectable_label(bypassed, "bypass").clicked() {
+                project.set_bypass(&self.selection, !bypassed);
+            }
+
+            macros::selection_menu(ui, self, project);
+            if ui.button("group").clicked() {
+                let groups = project.group(&self.selection);
+                self.select_ids(project, groups);
+            }
+            if ui.button("ungroup").clicked() {
+                let kept = self
+                    .selection
+                    .iter()
+                    .filter_map(|id| project.find(*id))
+                    .filter(|cation| {
+                        cation.modifier.modifier::<List>().is_some() && !List::can_ungroup(cation)
+                    })
+                    .count();
+                if kept > 0 {
+                    self.notifications.error(format!(
+                        "kept {kept} group(s) with a bypass, label or keyframes, \
+                        clear them to ungroup"
+                    ));
+                }
+                let ungrouped = project.ungroup(&self.selection);
+                self.select_ids(project, ungrouped);
+            }
+
+            ui.menu_button("🗑", |ui| {
+                if ui.button("sure?").clicked() {
+                    project.delete(&self.selection);
+                    self.selection.clear();
+                    self.selected = None;
+                    ui.close_menu();
+                }
+            });
+        });
+        ui.separator();
+    }
+
+    fn select_ids(&mut self, project: &Project, ids: Vec<Uuid>) {
+        self.selected = match ids.as_slice() {
+            [id] => project.find(*id).map(ModId::from_dyn_cation),
+            _ => None,
+        };
+        self.selection = ids.into_iter().collect();
     }
 
     pub fn select_cation(&mut self, cation: &Cation<DynMod>) {
         self.selected = Some(ModId::from_dyn_cation(cation));
+        self.selection = HashSet::from([cation.id]);
     }
 
     pub fn try_select_slot<'a>(&'a mut self, slot: &'a ModifierSlot) -> Result<(), &str> {
-        self.selected = Some(ModId::try_from_slot(slot)?);
+        let selected = ModId::try_from_slot(slot)?;
+        self.selection = HashSet::from([selected.id]);
+        self.selected = Some(selected);
         Ok(())
     }
 
+    /// Adds or removes a modifier from the selection, as with a ctrl click.
+    pub fn toggle_selected(&mut self, cation: &Cation<DynMod>) {
+        if self.selection.remove(&cation.id) {
+            if self.selected_id() == Some(cation.id) {
+                self.selected = None;
+            }
+        } else {
+            self.selection.insert(cation.id);
+            self.selected = Some(ModId::from_dyn_cation(cation));
+        }
+    }
+
+    pub fn is_selected(&self, id: Uuid) -> bool {
+        self.selection.contains(&id) || self.selected_id() == Some(id)
+    }
+
     pub fn selected_id(&self) -> Option<Uuid> {
         self.selected.as_ref().map(|selected| selected.id)
     }
 
-    pub fn is_modifier_selected<T: Modifier + Default + 'static>(&self) -> bool {
+    pub fn is_modifier_selected<T: Modifier + 'static>(&self) -> bool {
         self.selected
             .as_ref()
-            .is_some_and(|selected| selected.index == T::index())
+            .is_some_and(|selected| selected.index.id == TypeId::of::<T>())
     }
 }
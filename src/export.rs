@@ -0,0 +1,330 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+#[cfg(feature = "gui")]
+use eframe::egui::{self, Ui};
+use image::{
+    codecs::png::{self, PngEncoder},
+    DynamicImage, ImageEncoder, ImageFormat,
+};
+use jpeg_encoder::{ColorType, Encoder, SamplingFactor};
+use serde::{Deserialize, Serialize};
+
+use crate::image::Image;
+
+/// A file format images can be exported to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Png,
+    Jpeg,
+    Webp,
+    Tiff,
+    Bmp,
+    Tga,
+    Qoi,
+    Ico,
+    Gif,
+    Exr,
+}
+
+impl Format {
+    pub const ALL: [Self; 10] = [
+        Self::Png,
+        Self::Jpeg,
+        Self::Webp,
+        Self::Tiff,
+        Self::Bmp,
+        Self::Tga,
+        Self::Qoi,
+        Self::Ico,
+        Self::Gif,
+        Self::Exr,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Png => "PNG",
+            Self::Jpeg => "JPEG",
+            Self::Webp => "WebP (lossless)",
+            Self::Tiff => "TIFF",
+            Self::Bmp => "BMP",
+            Self::Tga => "TGA",
+            Self::Qoi => "QOI",
+            Self::Ico => "ICO (up to 256×256)",
+            Self::Gif => "GIF",
+            Self::Exr => "OpenEXR",
+        }
+    }
+
+    /// The file extensions, the first of which is given to new files.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Png => &["png"],
+            Self::Jpeg => &["jpg", "jpeg"],
+            Self::Webp => &["webp"],
+            Self::Tiff => &["tiff", "tif"],
+            Self::Bmp => &["bmp"],
+            Self::Tga => &["tga"],
+            Self::Qoi => &["qoi"],
+            Self::Ico => &["ico"],
+            Self::Gif => &["gif"],
+            Self::Exr => &["exr"],
+        }
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extensions().contains(&extension.as_str()))
+    }
+
+    pub fn has_alpha(self) -> bool {
+        self != Self::Jpeg
+    }
+
+    fn image_format(self) -> ImageFormat {
+        match self {
+            Self::Png => ImageFormat::Png,
+            Self::Jpeg => ImageFormat::Jpeg,
+            Self::Webp => ImageFormat::WebP,
+            Self::Tiff => ImageFormat::Tiff,
+            Self::Bmp => ImageFormat::Bmp,
+            Self::Tga => ImageFormat::Tga,
+            Self::Qoi => ImageFormat::Qoi,
+            Self::Ico => ImageFormat::Ico,
+            Self::Gif => ImageFormat::Gif,
+            Self::Exr => ImageFormat::OpenExr,
+        }
+    }
+}
+
+/// How much of the colour information JPEG keeps compared to the brightness.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Subsampling {
+    #[serde(rename = "4:4:4")]
+    None,
+    #[serde(rename = "4:2:2")]
+    Horizontal,
+    #[default]
+    #[serde(rename = "4:2:0")]
+    Both,
+}
+
+impl Subsampling {
+    pub const ALL: [Self; 3] = [Self::None, Self::Horizontal, Self::Both];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "4:4:4",
+            Self::Horizontal => "4:2:2",
+            Self::Both => "4:2:0",
+        }
+    }
+
+    fn factor(self) -> SamplingFactor {
+        match self {
+            Self::None => SamplingFactor::R_4_4_4,
+            Self::Horizontal => SamplingFactor::R_4_2_2,
+            Self::Both => SamplingFactor::R_4_2_0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    Fast,
+    #[default]
+    Default,
+    Best,
+}
+
+impl Compression {
+    pub const ALL: [Self; 3] = [Self::Fast, Self::Default, Self::Best];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fast => "fast",
+            Self::Default => "default",
+            Self::Best => "best",
+        }
+    }
+}
+
+/// How images are exported, remembered with the project.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    pub format: Format,
+    /// JPEG quality from 1 to 100.
+    pub quality: u8,
+    pub subsampling: Subsampling,
+    /// PNG compression level.
+    pub compression: Compression,
+    /// Whether PNG uses 16 bits per channel instead of 8.
+    pub sixteen_bit: bool,
+    /// Whether transparency is kept in formats that can store it.
+    pub keep_alpha: bool,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            format: Format::default(),
+            quality: 90,
+            subsampling: Subsampling::default(),
+            compression: Compression::default(),
+            sixteen_bit: false,
+            keep_alpha: true,
+        }
+    }
+}
+
+impl ExportSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Writes the image to the path in the format of these settings.
+    pub fn save(&self, image: &Image, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let keep_alpha = self.keep_alpha && self.format.has_alpha();
+        let image = match image.clone().into_dyn() {
+            image if keep_alpha => image,
+            // icons always have an alpha channel, so it is made opaque instead
+            image if self.format == Format::Ico => {
+                DynamicImage::ImageRgba8(DynamicImage::ImageRgb8(image.to_rgb8()).to_rgba8())
+            }
+            image => DynamicImage::ImageRgb8(image.to_rgb8()),
+        };
+
+        match self.format {
+            Format::Jpeg => {
+                let rgb = image.to_rgb8();
+                let too_large = |_| "image is too large for JPEG".to_string();
+                let width = u16::try_from(rgb.width()).map_err(too_large)?;
+                let height = u16::try_from(rgb.height()).map_err(too_large)?;
+
+                let mut encoder = Encoder::new_file(path, self.quality.clamp(1, 100))
+                    .map_err(|err| err.to_string())?;
+                encoder.set_sampling_factor(self.subsampling.factor());
+                encoder
+                    .encode(rgb.as_raw(), width, height, ColorType::Rgb)
+                    .map_err(|err| err.to_string())
+            }
+            Format::Png => {
+                let image = match (self.sixteen_bit, keep_alpha) {
+                    (true, true) => DynamicImage::ImageRgba16(image.to_rgba16()),
+                    (true, false) => DynamicImage::ImageRgb16(image.to_rgb16()),
+                    (false, _) => image,
+                };
+                let compression = match self.compression {
+                    Compression::Fast => png::CompressionType::Fast,
+                    Compression::Default => png::CompressionType::Default,
+                    Compression::Best => png::CompressionType::Best,
+                };
+
+                let file = File::create(path).map_err(|err| err.to_string())?;
+                PngEncoder::new_with_quality(
+                    BufWriter::new(file),
+                    compression,
+                    png::FilterType::Adaptive,
+                )
+                .write_image(
+                    image.as_bytes(),
+                    image.width(),
+                    image.height(),
+                    image.color(),
+                )
+                .map_err(|err| err.to_string())
+            }
+            // OpenEXR only stores floating point channels
+            Format::Exr => {
+                let image = if keep_alpha {
+                    DynamicImage::ImageRgba32F(image.to_rgba32f())
+                } else {
+                    DynamicImage::ImageRgb32F(image.to_rgb32f())
+                };
+                image
+                    .save_with_format(path, ImageFormat::OpenExr)
+                    .map_err(|err| err.to_string())
+            }
+            format => image
+                .save_with_format(path, format.image_format())
+                .map_err(|err| err.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl ExportSettings {
+    /// The format and the options it has.
+    pub fn view(&mut self, ui: &mut Ui) {
+        egui::Grid::new("export settings")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("format:");
+                egui::ComboBox::from_id_source("export format")
+                    .selected_text(self.format.name())
+                    .show_ui(ui, |ui| {
+                        for format in Format::ALL {
+                            ui.selectable_value(&mut self.format, format, format.name());
+                        }
+                    });
+                ui.end_row();
+
+                match self.format {
+                    Format::Jpeg => {
+                        ui.label("quality:");
+                        ui.add(egui::Slider::new(&mut self.quality, 1..=100));
+                        ui.end_row();
+
+                        ui.label("chroma subsampling:");
+                        egui::ComboBox::from_id_source("export subsampling")
+                            .selected_text(self.subsampling.name())
+                            .show_ui(ui, |ui| {
+                                for subsampling in Subsampling::ALL {
+                                    ui.selectable_value(
+                                        &mut self.subsampling,
+                                        subsampling,
+                                        subsampling.name(),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                    }
+                    Format::Png => {
+                        ui.label("compression:");
+                        egui::ComboBox::from_id_source("export compression")
+                            .selected_text(self.compression.name())
+                            .show_ui(ui, |ui| {
+                                for compression in Compression::ALL {
+                                    ui.selectable_value(
+                                        &mut self.compression,
+                                        compression,
+                                        compression.name(),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("bit depth:");
+                        ui.horizontal(|ui| {
+                            ui.selectable_value(&mut self.sixteen_bit, false, "8");
+                            ui.selectable_value(&mut self.sixteen_bit, true, "16");
+                        });
+                        ui.end_row();
+                    }
+                    _ => (),
+                }
+
+                if self.format.has_alpha() {
+                    ui.label("transparency:");
+                    ui.checkbox(&mut self.keep_alpha, "keep");
+                    ui.end_row();
+                }
+            });
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    fs,
     path::PathBuf,
     sync::mpsc::{channel, Receiver},
     thread::{self, JoinHandle},
@@ -6,35 +7,47 @@ use std::{
 
 use rfd::FileDialog;
 
-use crate::project::Project;
+use crate::{export::Format, notifications::Notifications, presets::Presets, project::Project};
+
+/// How many recent files are remembered.
+const RECENT_LIMIT: usize = 10;
 
 #[derive(Default)]
 pub struct FilePicker {
     handle: Option<JoinHandle<()>>,
     receiver: Option<Receiver<PickerResult>>,
+    /// Images and projects opened or saved before, the latest first.
+    recent: Vec<PathBuf>,
 }
 
 #[derive(Clone)]
 pub enum PickerResult {
     PickedLoad(PathBuf),
     PickedExport(PathBuf),
+    PickedProject(PathBuf),
+    PickedSave(PathBuf),
+    PickedPresetsImport(PathBuf),
+    PickedPresetsExport(PathBuf),
     Empty,
 }
 
 impl FilePicker {
     pub fn dialog_open() -> FileDialog {
-        FileDialog::new().add_filter("image", &["png", "jpg"])
+        let extensions = Format::ALL
+            .into_iter()
+            .flat_map(Format::extensions)
+            .collect::<Vec<_>>();
+        FileDialog::new().add_filter("image", &extensions)
     }
 
-    pub fn dialog_export(path: Option<PathBuf>) -> FileDialog {
-        let mut dialog = FileDialog::new()
-            .add_filter("png", &["png"])
-            .add_filter("jpg", &["jpg"]);
+    /// Saves next to the path with its name, in the format.
+    pub fn dialog_export(path: Option<PathBuf>, format: Format) -> FileDialog {
+        let mut dialog = FileDialog::new().add_filter(format.name(), format.extensions());
 
         if let Some(path) = path {
             dialog = dialog.set_directory(&path);
-            if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
-                dialog = dialog.set_file_name(name);
+            if let Some(name) = path.with_extension(format.extensions()[0]).file_name() {
+                dialog = dialog.set_file_name(name.to_string_lossy());
             }
         }
 
@@ -73,8 +86,8 @@ impl FilePicker {
         Ok(receiver)
     }
 
-    pub fn menu_export(&mut self, path: Option<PathBuf>) -> Result<(), &str> {
-        self.receiver = Some(self.picker_save(Self::dialog_export(path))?);
+    pub fn menu_export(&mut self, path: Option<PathBuf>, format: Format) -> Result<(), &str> {
+        self.receiver = Some(self.picker_save(Self::dialog_export(path, format))?);
 
         Ok(())
     }
@@ -85,6 +98,44 @@ impl FilePicker {
         Ok(())
     }
 
+    pub fn menu_open_project(&mut self) -> Result<(), &str> {
+        self.menu_json("project", false, PickerResult::PickedProject)
+    }
+
+    pub fn menu_save_project(&mut self) -> Result<(), &str> {
+        self.menu_json("project", true, PickerResult::PickedSave)
+    }
+
+    pub fn menu_import_presets(&mut self) -> Result<(), &str> {
+        self.menu_json("presets", false, PickerResult::PickedPresetsImport)
+    }
+
+    pub fn menu_export_presets(&mut self) -> Result<(), &str> {
+        self.menu_json("presets", true, PickerResult::PickedPresetsExport)
+    }
+
+    fn menu_json(
+        &mut self,
+        name: &str,
+        save: bool,
+        result: fn(PathBuf) -> PickerResult,
+    ) -> Result<(), &str> {
+        let (sender, receiver) = channel();
+        let dialog = FileDialog::new().add_filter(name, &["json"]);
+        self.spawn(move || {
+            let option = if save {
+                dialog.save_file()
+            } else {
+                dialog.pick_file()
+            };
+
+            sender.send(option.map_or(PickerResult::Empty, result)).ok();
+        })?;
+        self.receiver = Some(receiver);
+
+        Ok(())
+    }
+
     fn spawn(&mut self, closure: impl FnOnce() + Send + 'static) -> Result<(), &str> {
         if self.is_open() {
             return Err("picker is already open");
@@ -95,14 +146,44 @@ impl FilePicker {
         Ok(())
     }
 
-    pub fn update(&mut self, project: &mut Project) {
-        if let Some(receiver) = &self.receiver {
-            if let Ok(result) = receiver.try_recv() {
-                match result {
-                    PickerResult::PickedLoad(path) => *project = Project::new_from_input_path(path),
-                    PickerResult::PickedExport(path) => project.export(path).unwrap(),
-                    PickerResult::Empty => (),
+    /// Handles the file picked last, returning the project to switch to when one was opened.
+    pub fn update(
+        &mut self,
+        project: &mut Project,
+        presets: &mut Presets,
+        notifications: &mut Notifications,
+    ) -> Option<Project> {
+        let mut opened = None;
+        let result = self
+            .receiver
+            .as_ref()
+            .and_then(|receiver| receiver.try_recv().ok());
+
+        if let Some(result) = result {
+            match result {
+                PickerResult::PickedLoad(path) | PickerResult::PickedProject(path) => {
+                    opened = self.open(path, notifications)
+                }
+                PickerResult::PickedExport(path) => {
+                    if let Err(err) = project.export(path) {
+                        notifications.error(format!("failed to export image: {err}"));
+                    }
+                }
+                PickerResult::PickedSave(path) => match project.save(&path) {
+                    Ok(()) => self.remember(path, notifications),
+                    Err(err) => notifications.error(format!("failed to save project: {err}")),
+                },
+                PickerResult::PickedPresetsImport(path) => {
+                    if let Err(err) = presets.import(path) {
+                        notifications.error(format!("failed to import presets: {err}"));
+                    }
+                }
+                PickerResult::PickedPresetsExport(path) => {
+                    if let Err(err) = presets.export(path) {
+                        notifications.error(format!("failed to export presets: {err}"));
+                    }
                 }
+                PickerResult::Empty => (),
             }
         }
 
@@ -113,9 +194,78 @@ impl FilePicker {
         {
             self.handle.take().unwrap().join().ok();
         }
+
+        opened
     }
 
     pub fn is_open(&self) -> bool {
         self.handle.is_some()
     }
+
+    /// Opens a project file, or starts a new project from any other file as its input image.
+    pub fn open(&mut self, path: PathBuf, notifications: &mut Notifications) -> Option<Project> {
+        let project = if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            match Project::load(&path) {
+                Ok(loaded) => loaded,
+                Err(err) => {
+                    notifications.error(format!("failed to open project: {err}"));
+                    return None;
+                }
+            }
+        } else {
+            Project::new_from_input_path(&path)
+        };
+
+        self.remember(path, notifications);
+        Some(project)
+    }
+
+    pub fn recent(&self) -> &[PathBuf] {
+        &self.recent
+    }
+
+    pub fn recent_path() -> Option<PathBuf> {
+        Some(
+            dirs::config_dir()?
+                .join(env!("CARGO_PKG_NAME"))
+                .join("recent.json"),
+        )
+    }
+
+    /// Loads the files opened in earlier sessions.
+    pub fn load_recent(&mut self) -> Result<(), String> {
+        let Some(path) = Self::recent_path().filter(|path| path.exists()) else {
+            return Ok(());
+        };
+
+        let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        self.recent = serde_json::from_str(&text).map_err(|err| err.to_string())?;
+
+        Ok(())
+    }
+
+    fn save_recent(&self) -> Result<(), String> {
+        let path = Self::recent_path().ok_or("no config directory")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+
+        let text = serde_json::to_string_pretty(&self.recent).map_err(|err| err.to_string())?;
+        fs::write(path, text).map_err(|err| err.to_string())
+    }
+
+    /// Moves the file to the top of the recent files.
+    fn remember(&mut self, path: PathBuf, notifications: &mut Notifications) {
+        let path = path.canonicalize().unwrap_or(path);
+        self.recent.retain(|recent| *recent != path);
+        self.recent.insert(0, path);
+        self.recent.truncate(RECENT_LIMIT);
+
+        if let Err(err) = self.save_recent() {
+            notifications.error(format!("failed to save recent files: {err}"));
+        }
+    }
 }
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use crate::position::Position;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Guide {
+    Horizontal(f32),
+    Vertical(f32),
+}
+
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Guides {
+    pub lines: Vec<Guide>,
+}
+
+impl Guides {
+    pub fn horizontal(&self) -> impl Iterator<Item = f32> + '_ {
+        self.lines.iter().filter_map(|guide| match guide {
+            Guide::Horizontal(y) => Some(*y),
+            Guide::Vertical(_) => None,
+        })
+    }
+
+    pub fn vertical(&self) -> impl Iterator<Item = f32> + '_ {
+        self.lines.iter().filter_map(|guide| match guide {
+            Guide::Vertical(x) => Some(*x),
+            Guide::Horizontal(_) => None,
+        })
+    }
+
+    pub fn snap(&self, position: Position, threshold: f32) -> Position {
+        Position::new(
+            snap_value(position.x, self.vertical(), threshold),
+            snap_value(position.y, self.horizontal(), threshold),
+        )
+    }
+
+    /// Snaps a rectangle to the guides by whichever of its edges is closest, keeping its size.
+    pub fn snap_rect(&self, min: Position, size: Position, threshold: f32) -> Position {
+        let snapped_min = self.snap(min, threshold);
+        let snapped_max = self.snap(min + size, threshold) - size;
+
+        Position::new(
+            pick(min.x, snapped_min.x, snapped_max.x),
+            pick(min.y, snapped_min.y, snapped_max.y),
+        )
+    }
+}
+
+/// Prefers the snapped candidate closest to the original value, ignoring ones that did not snap.
+fn pick(value: f32, from_min: f32, from_max: f32) -> f32 {
+    match (from_min != value, from_max != value) {
+        (true, true) if (from_max - value).abs() < (from_min - value).abs() => from_max,
+        (true, _) => from_min,
+        (false, _) => from_max,
+    }
+}
+
+fn snap_value(value: f32, targets: impl Iterator<Item = f32>, threshold: f32) -> f32 {
+    targets
+        .map(|target| (target, (target - value).abs()))
+        .filter(|(_, distance)| *distance <= threshold)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(value, |(target, _)| target)
+}
@@ -0,0 +1,62 @@
+use crate::project::Project;
+
+/// How many changes can be undone.
+const LIMIT: usize = 100;
+
+/// Earlier states of the project, stored like project files, to undo and redo changes.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<String>,
+    redo: Vec<String>,
+    current: Option<String>,
+    /// The revision of the project that was recorded last.
+    revision: Option<u64>,
+}
+
+impl History {
+    /// Remembers the project if it changed since it was last recorded.
+    pub fn record(&mut self, project: &Project) {
+        if self.revision == Some(project.revision()) {
+            return;
+        }
+        self.revision = Some(project.revision());
+
+        let Ok(json) = project.to_json() else {
+            return;
+        };
+
+        if self.current.as_ref() == Some(&json) {
+            return;
+        }
+
+        if let Some(previous) = self.current.replace(json) {
+            self.undo.push(previous);
+            if self.undo.len() > LIMIT {
+                self.undo.remove(0);
+            }
+            self.redo.clear();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo(&mut self, project: &mut Project) -> Result<(), String> {
+        let previous = self.undo.pop().ok_or("nothing to undo")?;
+        project.restore(&previous)?;
+        self.redo.extend(self.current.replace(previous));
+        Ok(())
+    }
+
+    pub fn redo(&mut self, project: &mut Project) -> Result<(), String> {
+        let next = self.redo.pop().ok_or("nothing to redo")?;
+        project.restore(&next)?;
+        self.undo.extend(self.current.replace(next));
+        Ok(())
+    }
+}
@@ -1,21 +1,23 @@
-use std::path::Path;
+use std::{io::Cursor, path::Path, sync::Arc};
 
 use image::{
     imageops::{self, FilterType},
-    DynamicImage, ImageError, Rgba, RgbaImage,
+    DynamicImage, ImageError, ImageOutputFormat, Pixel, Rgba, RgbaImage,
 };
 
 use crate::{color::Color, position::Position};
 
+/// An image whose copies share their pixels until one of them is changed, so outputs can be
+/// cached and passed on without copying them.
 #[derive(Clone)]
 pub struct Image {
-    image: RgbaImage,
+    image: Arc<RgbaImage>,
 }
 
 impl Default for Image {
     fn default() -> Self {
         Self {
-            image: RgbaImage::new(1, 1),
+            image: Arc::new(RgbaImage::new(1, 1)),
         }
     }
 }
@@ -23,22 +25,28 @@ impl Default for Image {
 impl Image {
     pub fn from_dyn(image: DynamicImage) -> Self {
         Self {
-            image: image.into_rgba8(),
+            image: Arc::new(image.into_rgba8()),
         }
     }
 
     pub fn into_dyn(self) -> DynamicImage {
-        DynamicImage::ImageRgba8(self.image)
+        DynamicImage::ImageRgba8(Arc::unwrap_or_clone(self.image))
     }
 
     pub fn as_rgba8(&self) -> &RgbaImage {
         &self.image
     }
 
+    /// Copies the pixels first if they are shared with another image.
+    pub fn as_rgba8_mut(&mut self) -> &mut RgbaImage {
+        Arc::make_mut(&mut self.image)
+    }
+
     pub fn set_pixel(&mut self, position: Position, color: Color) -> Result<(), &str> {
         let (x, y) = position.try_into_u32()?;
         if self.contains_pixel(position) {
-            self.image.put_pixel(x, y, Rgba(color.into_rgba_u8()));
+            self.as_rgba8_mut()
+                .put_pixel(x, y, Rgba(color.into_rgba_u8()));
             Ok(())
         } else {
             Err("pixel outside image")
@@ -84,38 +92,83 @@ impl Image {
         self.image.save(path)
     }
 
+    /// Decodes an image file already read into memory.
+    pub fn from_memory(bytes: &[u8]) -> Result<Self, ImageError> {
+        Ok(Self::from_dyn(image::load_from_memory(bytes)?))
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, ImageError> {
+        let mut bytes = Cursor::new(Vec::new());
+        self.image.write_to(&mut bytes, ImageOutputFormat::Png)?;
+        Ok(bytes.into_inner())
+    }
+
     pub fn grayscale(&mut self) {
-        self.image = imageops::colorops::grayscale_with_type_alpha(&self.image);
+        self.image = Arc::new(imageops::colorops::grayscale_with_type_alpha(
+            self.as_rgba8(),
+        ));
     }
 
     pub fn huerotate(&mut self, degrees: i32) {
-        imageops::colorops::huerotate_in_place(&mut self.image, degrees);
+        imageops::colorops::huerotate_in_place(self.as_rgba8_mut(), degrees);
     }
 
     pub fn brighten(&mut self, value: i32) {
-        imageops::colorops::brighten_in_place(&mut self.image, value)
+        imageops::colorops::brighten_in_place(self.as_rgba8_mut(), value)
     }
 
     pub fn contrast(&mut self, value: f32) {
-        imageops::colorops::contrast_in_place(&mut self.image, value)
+        imageops::colorops::contrast_in_place(self.as_rgba8_mut(), value)
     }
 
     pub fn invert(&mut self) {
-        imageops::colorops::invert(&mut self.image);
+        imageops::colorops::invert(self.as_rgba8_mut());
     }
 
     pub fn blur(&mut self, sigma: f32) {
-        self.image = imageops::blur(&self.image, sigma)
+        self.image = Arc::new(imageops::blur(self.as_rgba8(), sigma))
     }
 
     pub fn resize(&mut self, new_size: Position, filter: FilterType) -> Result<(), &str> {
         let (x, y) = new_size.try_into_u32()?;
-        self.image = imageops::resize(&self.image, x, y, filter);
+        self.image = Arc::new(imageops::resize(self.as_rgba8(), x, y, filter));
         Ok(())
     }
 
     pub fn overlay(&mut self, overlay: &Image, position: Position) {
         let (x, y) = position.into_i32();
-        imageops::overlay(&mut self.image, &overlay.image, x.into(), y.into())
+        imageops::overlay(self.as_rgba8_mut(), overlay.as_rgba8(), x.into(), y.into())
     }
 }
+
+/// Calls `f` with the coordinates of every pixel, splitting the rows over the available threads.
+/// Each thread gets its own state from `init`, for buffers that would be wasteful to allocate
+/// per pixel.
+pub fn par_pixels_with<S>(
+    image: &mut RgbaImage,
+    init: impl Fn() -> S + Sync,
+    f: impl Fn(&mut S, u32, u32, &mut Rgba<u8>) + Sync,
+) {
+    let width = image.width() as usize;
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let rows = (image.height() as usize).div_ceil(threads).max(1);
+    let (init, f) = (&init, &f);
+
+    std::thread::scope(|scope| {
+        for (chunk_index, chunk) in image.chunks_mut(rows * width * 4).enumerate() {
+            scope.spawn(move || {
+                let mut state = init();
+                for (index, pixel) in chunk.chunks_exact_mut(4).enumerate() {
+                    let index = chunk_index * rows * width + index;
+                    let (x, y) = ((index % width) as u32, (index / width) as u32);
+                    f(&mut state, x, y, Rgba::from_slice_mut(pixel));
+                }
+            });
+        }
+    });
+}
+
+/// [`par_pixels_with`] without state.
+pub fn par_pixels(image: &mut RgbaImage, f: impl Fn(u32, u32, &mut Rgba<u8>) + Sync) {
+    par_pixels_with(image, || (), |_, x, y, pixel| f(x, y, pixel));
+}
@@ -0,0 +1,57 @@
+use eframe::egui::{self, Color32, Context, Sense, Ui, Vec2};
+use image_mod::{editor::Editor, image::Image, position::Position, project::Project};
+
+pub fn status_bar(ctx: &Context, editor: &Editor, project: &mut Project) {
+    egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            let Some(pixel) = editor.view.hovered_pixel(ctx) else {
+                ui.label("(no pixel hovered)");
+                return;
+            };
+
+            let position = Position::new(pixel.x.floor(), pixel.y.floor());
+            let (x, y) = position.into_i32();
+            ui.monospace(format!("x: {x} y: {y}"));
+
+            ui.separator();
+
+            ui.label("output:");
+            view_pixel(ui, project.output().as_ref(), position);
+
+            if let Some(selected) = editor.selected_id().and_then(|id| project.find(id)) {
+                ui.separator();
+
+                ui.label(format!("{}:", selected.modifier.index.name));
+                view_pixel(
+                    ui,
+                    selected
+                        .cached_output()
+                        .and_then(|output| output.image.as_ref()),
+                    position,
+                );
+            }
+        });
+    });
+}
+
+fn view_pixel(ui: &mut Ui, image: Option<&Image>, position: Position) {
+    let Some(color) = image.and_then(|image| image.pixel_at(position).ok()) else {
+        ui.label("-");
+        return;
+    };
+
+    let [r, g, b, a] = color.into_rgba_u8();
+    let (h, s, v) = color.into_hsv();
+
+    let (rect, _) =
+        ui.allocate_exact_size(Vec2::splat(ui.spacing().interact_size.y), Sense::hover());
+    ui.painter().rect_filled(rect, 2.0, Color32::from(color));
+
+    ui.monospace(format!(
+        "rgba({r}, {g}, {b}, {a}) {} hsv({:.0}°, {:.0}%, {:.0}%)",
+        color.to_hex(),
+        h.to_degrees(),
+        s * 100.0,
+        v * 100.0
+    ));
+}
@@ -1,22 +1,12 @@
-use eframe::egui::{Context, Key, ViewportCommand};
+//! Keys held down to change what dragging the image does. Actions bound to key presses are in
+//! [`crate::keymap`].
 
-pub fn fullscreen(ctx: &Context) {
-    if ctx.input(|input| input.key_pressed(Key::F11)) {
-        if ctx.input(|input| {
-            input
-                .viewport()
-                .fullscreen
-                .is_some_and(|fullscreen| fullscreen)
-        }) {
-            ctx.send_viewport_cmd(ViewportCommand::Fullscreen(false))
-        } else {
-            ctx.send_viewport_cmd(ViewportCommand::Fullscreen(true))
-        }
-    }
+use eframe::egui::{Context, Key};
+
+pub fn loupe(ctx: &Context) -> bool {
+    ctx.input(|input| input.key_down(Key::Z)) && !ctx.wants_keyboard_input()
 }
 
-pub fn exit(ctx: &Context) {
-    if ctx.input(|input| input.key_pressed(Key::Escape)) {
-        ctx.send_viewport_cmd(ViewportCommand::Close);
-    }
+pub fn pan(ctx: &Context) -> bool {
+    ctx.input(|input| input.key_down(Key::Space)) && !ctx.wants_keyboard_input()
 }
@@ -0,0 +1,571 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use eframe::egui::{
+    self, Context, Key, KeyboardShortcut, ModifierNames, Modifiers, ViewportCommand,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    editor::Editor,
+    modifier::{
+        cation::{Cation, DynMod},
+        collection::{
+            bucket::Bucket,
+            magic_wand::MagicWand,
+            pencil::{simple::SimplePencil, PencilMod},
+            source::Source,
+        },
+        traits::{Modifier, ModifierIndex},
+    },
+    palette::Scope,
+    project::Project,
+    slot::ModifierSlot,
+};
+
+/// Something a shortcut can do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    New,
+    Open,
+    Save,
+    Export,
+    Undo,
+    Redo,
+    DeleteSlot,
+    AddModifier,
+    Bypass,
+    CopyImage,
+    PasteImage,
+    CommandPalette,
+    QuickOpen,
+    ZoomIn,
+    ZoomOut,
+    Fit,
+    Fill,
+    ActualSize,
+    SelectPencil,
+    SelectBucket,
+    SelectMagicWand,
+    Fullscreen,
+    Shortcuts,
+    Exit,
+}
+
+impl Action {
+    pub const ALL: [Self; 24] = [
+        Self::New,
+        Self::Open,
+        Self::Save,
+        Self::Export,
+        Self::Undo,
+        Self::Redo,
+        Self::DeleteSlot,
+        Self::AddModifier,
+        Self::Bypass,
+        Self::CopyImage,
+        Self::PasteImage,
+        Self::CommandPalette,
+        Self::QuickOpen,
+        Self::ZoomIn,
+        Self::ZoomOut,
+        Self::Fit,
+        Self::Fill,
+        Self::ActualSize,
+        Self::SelectPencil,
+        Self::SelectBucket,
+        Self::SelectMagicWand,
+        Self::Fullscreen,
+        Self::Shortcuts,
+        Self::Exit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::New => "new project from image",
+            Self::Open => "open project",
+            Self::Save => "save project",
+            Self::Export => "export image",
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+            Self::DeleteSlot => "delete selected modifiers",
+            Self::AddModifier => "add modifier",
+            Self::Bypass => "bypass selected modifiers",
+            Self::CopyImage => "copy output image",
+            Self::PasteImage => "paste image",
+            Self::CommandPalette => "command palette",
+            Self::QuickOpen => "quick open",
+            Self::ZoomIn => "zoom in",
+            Self::ZoomOut => "zoom out",
+            Self::Fit => "zoom to fit",
+            Self::Fill => "zoom to fill",
+            Self::ActualSize => "actual size",
+            Self::SelectPencil => "pencil tool",
+            Self::SelectBucket => "bucket tool",
+            Self::SelectMagicWand => "magic wand tool",
+            Self::Fullscreen => "toggle fullscreen",
+            Self::Shortcuts => "shortcut reference",
+            Self::Exit => "exit",
+        }
+    }
+
+    fn default_shortcut(self) -> Option<KeyboardShortcut> {
+        let command = |key| KeyboardShortcut::new(Modifiers::COMMAND, key);
+        let plain = |key| KeyboardShortcut::new(Modifiers::NONE, key);
+
+        Some(match self {
+            Self::New => command(Key::N),
+            Self::Open => command(Key::O),
+            Self::Save => command(Key::S),
+            Self::Export => command(Key::E),
+            Self::Undo => command(Key::Z),
+            Self::Redo => KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z),
+            Self::DeleteSlot => plain(Key::Delete),
+            Self::AddModifier => KeyboardShortcut::new(Modifiers::SHIFT, Key::A),
+            Self::Bypass => command(Key::B),
+            Self::CopyImage => {
+                KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::C)
+            }
+            Self::PasteImage => {
+                KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::V)
+            }
+            Self::CommandPalette => {
+                KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::P)
+            }
+            Self::QuickOpen => command(Key::P),
+            Self::ZoomIn => command(Key::PlusEquals),
+            Self::ZoomOut => command(Key::Minus),
+            Self::Fit => command(Key::Num0),
+            Self::Fill => {
+                KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Num0)
+            }
+            Self::ActualSize => command(Key::Num1),
+            Self::SelectPencil => plain(Key::P),
+            Self::SelectBucket => plain(Key::B),
+            Self::SelectMagicWand => plain(Key::W),
+            Self::Fullscreen => plain(Key::F11),
+            Self::Shortcuts => plain(Key::F1),
+            Self::Exit => plain(Key::Escape),
+        })
+    }
+}
+
+/// The shortcut of every action, the defaults overridden by the user's keymap file.
+pub struct Keymap {
+    bindings: BTreeMap<Action, KeyboardShortcut>,
+    /// Problems with the user's keymap file.
+    pub errors: Vec<String>,
+    /// Whether the shortcut reference is shown.
+    pub open: bool,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .into_iter()
+                .filter_map(|action| Some((action, action.default_shortcut()?)))
+                .collect(),
+            errors: Vec::new(),
+            open: false,
+        }
+    }
+}
+
+impl Keymap {
+    /// Where the user can rebind actions, with json like `{ "undo": "Ctrl+Z", "exit": null }`.
+    pub fn user_path() -> Option<PathBuf> {
+        Some(
+            dirs::config_dir()?
+                .join(env!("CARGO_PKG_NAME"))
+                .join("keymap.json"),
+        )
+    }
+
+    /// The default keymap with the user's changes, keeping the defaults for bindings that
+    /// could not be read.
+    pub fn load() -> Self {
+        let mut keymap = Self::default();
+
+        let Some(path) = Self::user_path().filter(|path| path.exists()) else {
+            return keymap;
+        };
+
+        let overrides = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|json| {
+                serde_json::from_str::<BTreeMap<Action, Option<String>>>(&json)
+                    .map_err(|err| err.to_string())
+            });
+
+        match overrides {
+            Ok(overrides) => {
+                for (action, text) in overrides {
+                    match text.map(|text| parse_shortcut(&text)).transpose() {
+                        Ok(Some(shortcut)) => {
+                            keymap.bindings.insert(action, shortcut);
+                        }
+                        Ok(None) => {
+                            keymap.bindings.remove(&action);
+                        }
+                        Err(err) => keymap.errors.push(format!("{}: {err}", action.name())),
+                    }
+                }
+            }
+            Err(err) => keymap.errors.push(format!("{}: {err}", path.display())),
+        }
+
+        keymap
+    }
+
+    pub fn shortcut(&self, action: Action) -> Option<KeyboardShortcut> {
+        self.bindings.get(&action).copied()
+    }
+
+    /// The shortcut of the action as text, for menus.
+    pub fn format(&self, action: Action) -> String {
+        self.shortcut(action)
+            .map_or(String::new(), |shortcut| format_shortcut(&shortcut))
+    }
+
+    /// Shortcuts bound to more than one action, of which only the first would run.
+    pub fn conflicts(&self) -> Vec<(KeyboardShortcut, Vec<Action>)> {
+        let mut actions = BTreeMap::<String, (KeyboardShortcut, Vec<Action>)>::new();
+        for (action, shortcut) in &self.bindings {
+            actions
+                .entry(format_shortcut(shortcut))
+                .or_insert((*shortcut, Vec::new()))
+                .1
+                .push(*action);
+        }
+
+        actions
+            .into_values()
+            .filter(|(_, actions)| actions.len() > 1)
+            .collect()
+    }
+
+    /// The actions whose shortcut was pressed, consuming the key presses. Nothing is pressed
+    /// while typing in a text field.
+    pub fn pressed(&self, ctx: &Context) -> Vec<Action> {
+        if ctx.wants_keyboard_input() {
+            return Vec::new();
+        }
+
+        self.bindings
+            .iter()
+            .filter(|(_, shortcut)| ctx.input_mut(|input| input.consume_shortcut(shortcut)))
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    /// Window listing every action with its shortcut and any conflicts.
+    pub fn view(&mut self, ctx: &Context) {
+        let conflicts = self.conflicts();
+
+        egui::Window::new("shortcuts")
+            .open(&mut self.open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("shortcuts").striped(true).show(ui, |ui| {
+                    for action in Action::ALL {
+                        ui.label(action.name());
+
+                        let conflicting = conflicts
+                            .iter()
+                            .any(|(_, actions)| actions.contains(&action));
+                        let text = self
+                            .bindings
+                            .get(&action)
+                            .map_or("(none)".to_string(), format_shortcut);
+
+                        if conflicting {
+                            ui.colored_label(ui.visuals().error_fg_color, text)
+                                .on_hover_text("bound to more than one action");
+                        } else {
+                            ui.monospace(text);
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                for error in &self.errors {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                if let Some(path) = Self::user_path() {
+                    ui.separator();
+                    ui.weak(format!("change them in {}", path.display()));
+                }
+            });
+    }
+}
+
+/// Parses shortcuts like `Ctrl+Shift+Z` or `F11`. `Ctrl` and `Cmd` both mean the command key
+/// of the platform.
+pub fn parse_shortcut(text: &str) -> Result<KeyboardShortcut, String> {
+    let mut modifiers = Modifiers::NONE;
+    let mut parts = text.split('+').map(str::trim).collect::<Vec<_>>();
+
+    // `Ctrl++` ends with an empty part for the plus key
+    if text.ends_with("++") {
+        parts.pop();
+        *parts.last_mut().unwrap() = "Plus";
+    }
+
+    let (key, parts) = parts
+        .split_last()
+        .ok_or_else(|| "empty shortcut".to_string())?;
+
+    for part in parts {
+        match part.to_lowercase().as_str() {
+            "ctrl" | "cmd" | "command" => modifiers = modifiers.plus(Modifiers::COMMAND),
+            "shift" => modifiers = modifiers.plus(Modifiers::SHIFT),
+            "alt" | "option" => modifiers = modifiers.plus(Modifiers::ALT),
+            _ => return Err(format!("unknown modifier `{part}`")),
+        }
+    }
+
+    let key = KEYS
+        .into_iter()
+        .find(|candidate| candidate.name().eq_ignore_ascii_case(key))
+        .ok_or_else(|| format!("unknown key `{key}`"))?;
+
+    Ok(KeyboardShortcut::new(modifiers, key))
+}
+
+pub fn format_shortcut(shortcut: &KeyboardShortcut) -> String {
+    shortcut.format(&ModifierNames::NAMES, cfg!(target_os = "macos"))
+}
+
+/// Runs the actions whose shortcuts were pressed.
+pub fn run(ctx: &Context, editor: &mut Editor, project: &mut Project) {
+    for action in editor.keymap.pressed(ctx) {
+        perform(action, ctx, editor, project);
+    }
+}
+
+pub fn perform(action: Action, ctx: &Context, editor: &mut Editor, project: &mut Project) {
+    const ZOOM_FACTOR: f32 = 1.3;
+
+    match action {
+        Action::New => {
+            editor.picker.menu_new().ok();
+        }
+        Action::Open => {
+            editor.picker.menu_open_project().ok();
+        }
+        Action::Save => {
+            editor.picker.menu_save_project().ok();
+        }
+        Action::Export => editor.exporting = true,
+        Action::Undo => {
+            if let Err(err) = editor.history.undo(project) {
+                editor.notifications.error(format!("failed to undo: {err}"));
+            }
+        }
+        Action::Redo => {
+            if let Err(err) = editor.history.redo(project) {
+                editor.notifications.error(format!("failed to redo: {err}"));
+            }
+        }
+        Action::DeleteSlot => {
+            project.delete(&editor.selection);
+            editor.selection.clear();
+            editor.selected = None;
+        }
+        Action::AddModifier => editor.palette.open(Scope::Modifiers),
+        Action::Bypass => {
+            let bypassed = editor
+                .selection
+                .iter()
+                .all(|id| project.find(*id).is_some_and(|cation| cation.bypass));
+            project.set_bypass(&editor.selection, !bypassed);
+        }
+        Action::CopyImage => {
+            if let Some(output) = project.output() {
+                if let Err(err) = editor.clipboard.copy_image(output) {
+                    editor
+                        .notifications
+                        .error(format!("failed to copy image: {err}"));
+                }
+            }
+        }
+        Action::PasteImage => match editor.clipboard.paste_image().and_then(Source::embedded) {
+            Ok(source) => {
+                if project.root.modifier.contents.is_empty() {
+                    editor.view.request_fit();
+                }
+                editor.insert_after_selected(Cation::new(DynMod::new(source)));
+            }
+            Err(err) => editor
+                .notifications
+                .error(format!("failed to paste image: {err}")),
+        },
+        Action::CommandPalette => editor.palette.open(Scope::Commands),
+        Action::QuickOpen => editor.palette.open(Scope::QuickOpen),
+        Action::ZoomIn => editor.view.zoom_by(ZOOM_FACTOR),
+        Action::ZoomOut => editor.view.zoom_by(1.0 / ZOOM_FACTOR),
+        Action::Fit => editor.view.fit(ctx),
+        Action::Fill => editor.view.fill(ctx),
+        Action::ActualSize => editor.view.actual_size(),
+        Action::SelectPencil => select_tool(editor, project, PencilMod::<SimplePencil>::index()),
+        Action::SelectBucket => select_tool(editor, project, Bucket::index()),
+        Action::SelectMagicWand => select_tool(editor, project, MagicWand::index()),
+        Action::Fullscreen => {
+            let fullscreen = ctx.input(|input| input.viewport().fullscreen.unwrap_or(false));
+            ctx.send_viewport_cmd(ViewportCommand::Fullscreen(!fullscreen));
+        }
+        Action::Shortcuts => editor.keymap.open = !editor.keymap.open,
+        Action::Exit => ctx.send_viewport_cmd(ViewportCommand::Close),
+    }
+}
+
+/// Selects the last modifier of the type in the project's list, adding one if there is none.
+fn select_tool(editor: &mut Editor, project: &mut Project, index: ModifierIndex) {
+    let existing = project
+        .root
+        .modifier
+        .iter_mods()
+        .filter(|cation| cation.modifier.index == index)
+        .last();
+
+    match existing {
+        Some(cation) => editor.select_cation(cation),
+        None => {
+            let cation = Cation::new(DynMod::from_index(index));
+            editor.select_cation(&cation);
+            project
+                .root
+                .modifier
+                .contents
+                .push(ModifierSlot::from_cacher(cation));
+        }
+    }
+}
+
+/// Every key a shortcut can use.
+const KEYS: [Key; 73] = [
+    Key::ArrowDown,
+    Key::ArrowLeft,
+    Key::ArrowRight,
+    Key::ArrowUp,
+    Key::Escape,
+    Key::Tab,
+    Key::Backspace,
+    Key::Enter,
+    Key::Space,
+    Key::Insert,
+    Key::Delete,
+    Key::Home,
+    Key::End,
+    Key::PageUp,
+    Key::PageDown,
+    Key::Minus,
+    Key::PlusEquals,
+    Key::Num0,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+    Key::F13,
+    Key::F14,
+    Key::F15,
+    Key::F16,
+    Key::F17,
+    Key::F18,
+    Key::F19,
+    Key::F20,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_shortcuts() {
+        assert_eq!(
+            parse_shortcut("Ctrl+Shift+Z"),
+            Ok(KeyboardShortcut::new(
+                Modifiers::COMMAND.plus(Modifiers::SHIFT),
+                Key::Z
+            ))
+        );
+        assert_eq!(
+            parse_shortcut("alt + f11"),
+            Ok(KeyboardShortcut::new(Modifiers::ALT, Key::F11))
+        );
+        assert_eq!(
+            parse_shortcut("Escape"),
+            Ok(KeyboardShortcut::new(Modifiers::NONE, Key::Escape))
+        );
+    }
+
+    #[test]
+    fn parse_plus() {
+        assert_eq!(
+            parse_shortcut("Ctrl++"),
+            Ok(KeyboardShortcut::new(Modifiers::COMMAND, Key::PlusEquals))
+        );
+        assert_eq!(
+            parse_shortcut("Ctrl+Plus"),
+            Ok(KeyboardShortcut::new(Modifiers::COMMAND, Key::PlusEquals))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_shortcut("Hyper+A"),
+            Err("unknown modifier `Hyper`".to_string())
+        );
+        assert_eq!(
+            parse_shortcut("Ctrl+Nope"),
+            Err("unknown key `Nope`".to_string())
+        );
+        assert!(parse_shortcut("").is_err());
+    }
+}
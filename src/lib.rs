@@ -0,0 +1,42 @@
+#![allow(incomplete_features)]
+#![feature(trait_upcasting)]
+
+#[cfg(feature = "gui")]
+pub mod clipboard;
+pub mod color;
+#[cfg(feature = "gui")]
+pub mod drag_drop;
+#[cfg(feature = "gui")]
+pub mod editor;
+pub mod export;
+#[cfg(feature = "gui")]
+pub mod file_picker;
+pub mod guides;
+pub mod history;
+pub mod image;
+#[cfg(feature = "gui")]
+pub mod keybinds;
+#[cfg(feature = "gui")]
+pub mod keymap;
+pub mod modifier;
+#[cfg(feature = "gui")]
+pub mod notifications;
+#[cfg(feature = "gui")]
+pub mod palette;
+pub mod plugin;
+pub mod position;
+pub mod presets;
+#[cfg(feature = "gui")]
+pub mod preview;
+#[cfg(feature = "gui")]
+pub mod profiler;
+pub mod project;
+pub mod slot;
+#[cfg(feature = "gui")]
+pub mod thumbnails;
+#[cfg(feature = "gui")]
+pub mod timeline;
+#[cfg(feature = "gui")]
+pub mod usage;
+#[cfg(feature = "gui")]
+pub mod view;
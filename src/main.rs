@@ -1,31 +1,23 @@
-#![allow(incomplete_features)]
-#![feature(trait_upcasting)]
-
 use std::path::Path;
 
-use editor::Editor;
 use eframe::{
     egui::{self, Context},
     epaint::Vec2,
     Frame,
 };
+use image_mod::{
+    drag_drop,
+    editor::Editor,
+    image::Image,
+    keymap::{self, Keymap},
+    modifier, plugin,
+    project::Project,
+    usage::Usage,
+};
 use menu::menu;
-use project::Project;
-
-use crate::image::Image;
 
-mod applied;
-mod color;
-mod editor;
-mod file_picker;
-mod image;
-mod keybinds;
+mod inspector;
 mod menu;
-mod modifier;
-mod position;
-mod project;
-mod slot;
-mod view;
 
 #[derive(Default)]
 struct App {
@@ -37,16 +29,27 @@ impl eframe::App for App {
     fn update(&mut self, ctx: &Context, _: &mut Frame) {
         let App { project, editor } = self;
 
-        keybinds::fullscreen(ctx);
-        keybinds::exit(ctx);
+        keymap::run(ctx, editor, project);
+
+        if let Some(opened) =
+            editor
+                .picker
+                .update(project, &mut editor.presets, &mut editor.notifications)
+        {
+            editor.replace_project(project, opened);
+        }
 
-        editor.picker.update(project);
+        menu(ctx, editor, project);
 
-        menu(ctx, &mut editor.view, project, &mut editor.picker);
+        inspector::status_bar(ctx, editor, project);
 
         editor.view(ctx, project);
 
-        if project.output_changed() {
+        drag_drop::handle(ctx, editor, project);
+
+        let previewing = editor.preview.show(ctx, project, &mut editor.view);
+
+        if !previewing && (project.output_changed() || editor.view.needs_refresh()) {
             let default = Image::default();
 
             editor
@@ -54,7 +57,7 @@ impl eframe::App for App {
                 .update(ctx, project.output().as_ref().unwrap_or(&default));
         }
 
-        editor.view.process(ctx);
+        editor.view.process(ctx, &mut project.guides);
     }
 }
 
@@ -67,12 +70,48 @@ fn main() {
         ..Default::default()
     };
 
-    let project = if let Ok(path) = std::env::var("NEW_PROJECT_INPUT_PATH") {
-        Project::new_from_input_path(Path::new(&path))
-    } else {
-        Project::default()
+    let mut input = None;
+    let mut overrides = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--param" => match args.next() {
+                Some(assignment) => overrides.push(assignment),
+                None => usage_error("--param needs an assignment"),
+            },
+            "--help" | "-h" => {
+                println!("{}", usage());
+                return;
+            }
+            flag if flag.starts_with('-') => usage_error(&format!("unknown option {flag}")),
+            _ if input.is_some() => usage_error(&format!("unexpected argument {arg}")),
+            _ => input = Some(arg),
+        }
+    }
+    let input = input.or_else(|| std::env::var("NEW_PROJECT_INPUT_PATH").ok());
+
+    // shown once the window is open
+    let mut errors = plugin::load_plugins()
+        .into_iter()
+        .map(|err| format!("failed to load plugin {err}"))
+        .collect::<Vec<_>>();
+
+    let mut project = match input {
+        Some(path) if path.ends_with(".json") => Project::load(&path).unwrap_or_else(|err| {
+            errors.push(format!("failed to open project: {err}"));
+            Project::default()
+        }),
+        Some(path) => Project::new_from_input_path(Path::new(&path)),
+        None => Project::default(),
     };
 
+    for assignment in overrides {
+        if let Err(err) = project.override_param(&assignment) {
+            errors.push(format!("--param {assignment}: {err}"));
+        }
+    }
+
     eframe::run_native(
         env!("CARGO_PKG_NAME"),
         options,
@@ -80,14 +119,59 @@ fn main() {
             cc.egui_ctx.set_pixels_per_point(1.5);
 
             let mut editor = Editor::default();
+            for err in errors {
+                editor.notifications.error(err);
+            }
+            editor.keymap = Keymap::load();
+            for err in &editor.keymap.errors {
+                editor
+                    .notifications
+                    .error(format!("failed to read keymap: {err}"));
+            }
+            for (shortcut, actions) in editor.keymap.conflicts() {
+                let actions = actions
+                    .iter()
+                    .map(|action| action.name())
+                    .collect::<Vec<_>>();
+                editor.notifications.error(format!(
+                    "shortcut {} is bound to {}",
+                    keymap::format_shortcut(&shortcut),
+                    actions.join(", ")
+                ));
+            }
             modifier::collection::init_modifiers_collection(&mut editor);
-
-            Box::new(App {
-                project,
-                editor,
-                ..Default::default()
-            })
+            if let Err(err) = editor.presets.load_user() {
+                editor
+                    .notifications
+                    .error(format!("failed to load presets: {err}"));
+            }
+            if let Err(err) = editor.picker.load_recent() {
+                editor
+                    .notifications
+                    .error(format!("failed to load recent files: {err}"));
+            }
+            match Usage::load() {
+                Ok(usage) => editor.usage = usage,
+                Err(err) => editor
+                    .notifications
+                    .error(format!("failed to load modifier usage: {err}")),
+            }
+
+            Box::new(App { project, editor })
         }),
     )
     .unwrap();
 }
+
+fn usage() -> String {
+    format!(
+        "usage: {} [IMAGE | PROJECT.json] [--param TYPE.PARAM=VALUE]...",
+        env!("CARGO_PKG_NAME")
+    )
+}
+
+/// Prints the error with the usage and exits, before the window is opened.
+fn usage_error(message: &str) -> ! {
+    eprintln!("{message}\n{}", usage());
+    std::process::exit(2);
+}
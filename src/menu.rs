@@ -1,22 +1,139 @@
-use eframe::egui::{self, Context, Vec2};
+use eframe::egui::{self, Color32, Context, Ui, Vec2};
+use image_mod::{
+    editor::Editor,
+    keymap::{self, Action},
+    modifier::collection::generator::MAX_SIZE,
+    position::Position,
+    presets::Presets,
+    project::Project,
+    view::{Background, Display, View},
+};
 
-use crate::{file_picker::FilePicker, project::Project, view::View};
+pub fn menu(ctx: &Context, editor: &mut Editor, project: &mut Project) {
+    let mut action = None;
+    let mut replacement = None;
+
+    let Editor {
+        keymap,
+        history,
+        view,
+        picker: file_picker,
+        profiler,
+        timeline,
+        exporting,
+        ..
+    } = editor;
 
-pub fn menu(ctx: &Context, view: &mut View, project: &Project, file_picker: &mut FilePicker) {
     egui::TopBottomPanel::top("panel").show(ctx, |ui| {
         egui::menu::bar(ui, |ui| {
             ui.add_enabled_ui(!file_picker.is_open(), |ui| {
-                if ui.button("new").clicked() {
-                    file_picker.menu_new().ok();
+                ui.menu_button("new", |ui| {
+                    if ui.button("from image…").clicked() {
+                        file_picker.menu_new().ok();
+                        ui.close_menu();
+                    }
+
+                    ui.separator();
+
+                    let id = egui::Id::new("blank canvas size");
+                    let mut size = ui
+                        .data(|data| data.get_temp::<Vec2>(id))
+                        .unwrap_or(Vec2::splat(512.0));
+
+                    ui.label("blank canvas");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut size.x).clamp_range(1.0..=MAX_SIZE as f32),
+                        );
+                        ui.label("×");
+                        ui.add(
+                            egui::DragValue::new(&mut size.y).clamp_range(1.0..=MAX_SIZE as f32),
+                        );
+                    });
+                    ui.data_mut(|data| data.insert_temp(id, size));
+
+                    if ui.button("create").clicked() {
+                        replacement = Some(Project::new_blank(Position::new(
+                            size.x.round(),
+                            size.y.round(),
+                        )));
+                        ui.close_menu();
+                    }
+                });
+            });
+
+            ui.add_enabled_ui(!file_picker.is_open(), |ui| {
+                if ui.button("open").clicked() {
+                    file_picker.menu_open_project().ok();
+                }
+
+                if ui.button("save").clicked() {
+                    file_picker.menu_save_project().ok();
                 }
             });
 
-            ui.add_enabled_ui(project.path().is_some() && !file_picker.is_open(), |ui| {
+            ui.add_enabled_ui(!file_picker.is_open(), |ui| {
                 if ui.button("export").clicked() {
-                    file_picker.menu_export(project.path()).ok();
+                    *exporting = true;
                 }
             });
 
+            ui.menu_button("edit", |ui| {
+                let mut item = |ui: &mut Ui, enabled: bool, name: Action| {
+                    let button = egui::Button::new(name.name()).shortcut_text(keymap.format(name));
+                    if ui.add_enabled(enabled, button).clicked() {
+                        action = Some(name);
+                        ui.close_menu();
+                    }
+                };
+
+                item(ui, history.can_undo(), Action::Undo);
+                item(ui, history.can_redo(), Action::Redo);
+                ui.separator();
+                item(ui, true, Action::AddModifier);
+                item(ui, true, Action::DeleteSlot);
+                item(ui, true, Action::Bypass);
+                ui.separator();
+                item(ui, true, Action::CopyImage);
+                item(ui, true, Action::PasteImage);
+            });
+
+            ui.menu_button("presets", |ui| {
+                ui.add_enabled_ui(!file_picker.is_open(), |ui| {
+                    if ui.button("import").clicked() {
+                        file_picker.menu_import_presets().ok();
+                        ui.close_menu();
+                    }
+
+                    if ui.button("export").clicked() {
+                        file_picker.menu_export_presets().ok();
+                        ui.close_menu();
+                    }
+                });
+
+                if let Some(path) = Presets::user_path() {
+                    ui.weak(path.to_string_lossy());
+                }
+            });
+
+            ui.menu_button("view", |ui| {
+                ui.checkbox(&mut view.show_grid, "pixel grid");
+                ui.checkbox(&mut view.show_rulers, "rulers");
+                ui.checkbox(&mut view.show_navigator, "navigator");
+
+                ui.menu_button("background", |ui| background_menu(ui, view));
+
+                ui.checkbox(&mut profiler.open, "profiler");
+                ui.checkbox(&mut timeline.open, "timeline");
+
+                ui.add_enabled_ui(!project.guides.lines.is_empty(), |ui| {
+                    if ui.button("clear guides").clicked() {
+                        project.guides.lines.clear();
+                        ui.close_menu();
+                    }
+                });
+            });
+
             ui.separator();
 
             let mut percentage = view.scale * 100.0;
@@ -40,6 +157,22 @@ pub fn menu(ctx: &Context, view: &mut View, project: &Project, file_picker: &mut
 
             view.scale = percentage / 100.0;
 
+            if ui.button("fit").clicked() {
+                view.fit(ctx);
+            }
+
+            if ui.button("fill").clicked() {
+                view.fill(ctx);
+            }
+
+            if ui.button("1:1").clicked() {
+                view.actual_size();
+            }
+
+            if ui.button("shortcuts").clicked() {
+                keymap.open = !keymap.open;
+            }
+
             ui.separator();
 
             {
@@ -49,6 +182,71 @@ pub fn menu(ctx: &Context, view: &mut View, project: &Project, file_picker: &mut
                     ui.label("(no image)");
                 }
             }
+
+            if project.is_modified() {
+                ui.weak("(unsaved)");
+            }
         });
     });
+
+    if let Some(replacement) = replacement {
+        editor.confirm_replace_project(project, replacement);
+    }
+
+    if let Some(action) = action {
+        keymap::perform(action, ctx, editor, project);
+    }
+}
+
+fn background_menu(ui: &mut Ui, view: &mut View) {
+    let mut display = view.display();
+    ui.radio_value(&mut display, Display::Color, "color");
+    ui.radio_value(&mut display, Display::AlphaOnly, "alpha only");
+    view.set_display(display);
+
+    ui.separator();
+
+    ui.radio_value(
+        &mut view.background,
+        Background::Checkerboard,
+        "checkerboard",
+    );
+    ui.add_enabled_ui(view.background == Background::Checkerboard, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("colors:");
+            ui.color_edit_button_srgba(&mut view.checkerboard.colors[0]);
+            ui.color_edit_button_srgba(&mut view.checkerboard.colors[1]);
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("size:");
+            ui.add(
+                egui::DragValue::new(&mut view.checkerboard.size)
+                    .clamp_range(4.0..=128.0)
+                    .suffix("pt"),
+            );
+        });
+    });
+
+    ui.separator();
+
+    for (color, name) in [
+        (Color32::BLACK, "black"),
+        (Color32::WHITE, "white"),
+        (Color32::GRAY, "gray"),
+    ] {
+        ui.radio_value(&mut view.background, Background::Solid(color), name);
+    }
+
+    ui.horizontal(|ui| {
+        let mut color = match view.background {
+            Background::Solid(color) => color,
+            Background::Checkerboard => Color32::BLACK,
+        };
+
+        ui.label("custom:");
+        if ui.color_edit_button_srgba(&mut color).changed() {
+            view.background = Background::Solid(color);
+        }
+    });
 }
@@ -1,14 +1,53 @@
+use std::{
+    any::TypeId,
+    cell::Cell,
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "gui")]
 use eframe::egui::Ui;
 use uuid::Uuid;
 
-use super::traits::{DynPartialEq, Modifier, ModifierIndex};
-use crate::{editor::Editor, image::Image};
+use super::{
+    collection::reference::Reference,
+    keyframes::Keyframes,
+    label::Label,
+    param::{Param, Value},
+    traits::{DynPartialEq, Modifier, ModifierIndex},
+};
+#[cfg(feature = "gui")]
+use crate::editor::Editor;
+use crate::{image::Image, slot::ModifierSlot};
 
 #[derive(Clone)]
 pub struct Cation<T> {
     pub id: Uuid,
     pub modifier: T,
     cache: Option<Cache<T>>,
+    pub stats: Stats,
+    pub keyframes: Keyframes,
+    /// Passes the input on unchanged, to compare with and without the modifier.
+    pub bypass: bool,
+    pub label: Label,
+}
+
+#[derive(Clone, Default)]
+pub struct Stats {
+    /// How long the last recompute took, including nested slots.
+    pub last: Duration,
+    /// How long the last recompute took without the nested slots recomputed in it.
+    pub last_self: Duration,
+    pub total: Duration,
+    pub total_self: Duration,
+    pub recomputes: u32,
+    pub hits: u32,
+}
+
+thread_local! {
+    /// Time spent recomputing in the modifier being applied, to subtract it from its parent's.
+    static NESTED: Cell<Duration> = const { Cell::new(Duration::ZERO) };
 }
 
 impl<T: Modifier + Clone + PartialEq> Cation<T> {
@@ -17,32 +56,134 @@ impl<T: Modifier + Clone + PartialEq> Cation<T> {
             id: Uuid::new_v4(),
             modifier,
             cache: None,
+            stats: Stats::default(),
+            keyframes: Keyframes::default(),
+            bypass: false,
+            label: Label::default(),
         }
     }
 
     pub fn check_cache(&self, input: &Output) -> bool {
-        self.cache
-            .as_ref()
-            .is_some_and(|cache| !cache.changed(&self.modifier) && cache.input_id == input.id)
+        self.cache.as_ref().is_some_and(|cache| {
+            !cache.changed(&self.modifier)
+                && cache.bypass == self.bypass
+                && cache.input_id == input.id
+        })
     }
 
     pub fn output(&mut self, input: &Output) -> &Output {
         if self.check_cache(input) {
+            self.stats.hits += 1;
             return &self.cache.as_ref().unwrap().output;
         }
 
         self.apply(input)
     }
 
+    pub fn cached_output(&self) -> Option<&Output> {
+        self.cache.as_ref().map(|cache| &cache.output)
+    }
+
+    /// Bytes held by the cached output image.
+    pub fn cache_memory(&self) -> usize {
+        self.cached_output()
+            .and_then(|output| output.image.as_ref())
+            .map_or(0, |image| image.as_rgba8().as_raw().len())
+    }
+
+    /// Every modifier nested inside this one, depth first.
+    pub fn descendants(&self) -> Vec<&Cation<DynMod>> {
+        self.modifier
+            .slots()
+            .into_iter()
+            .flat_map(|slot| slot.mod_ref())
+            .flat_map(|cation| std::iter::once(cation).chain(cation.descendants()))
+            .collect()
+    }
+
+    /// Gives this modifier and everything nested in it new ids, for copies that live next to
+    /// the original. References within the copy are kept pointing into it.
+    pub fn renew_ids(&mut self) {
+        let mut renewed = HashMap::new();
+
+        self.id = Uuid::new_v4();
+        self.for_each_descendant_mut(&mut |cation| {
+            let id = Uuid::new_v4();
+            renewed.insert(std::mem::replace(&mut cation.id, id), id);
+        });
+
+        self.for_each_descendant_mut(&mut |cation| {
+            if let Some(reference) = cation.modifier.modifier_mut::<Reference>() {
+                if let Some(id) = reference.target.and_then(|target| renewed.get(&target)) {
+                    reference.target = Some(*id);
+                }
+            }
+        });
+    }
+
+    pub fn for_each_descendant_mut(&mut self, f: &mut impl FnMut(&mut Cation<DynMod>)) {
+        for slot in self.modifier.slots_mut() {
+            if let Some(cation) = slot.mod_mut() {
+                f(cation);
+                cation.for_each_descendant_mut(f);
+            }
+        }
+    }
+
+    /// Sets every keyframed parameter, including those of nested modifiers, to its value at the
+    /// given time.
+    pub fn animate(&mut self, time: f32) {
+        for (name, _) in self.keyframes.tracks() {
+            if let Some(value) = self.keyframes.sample(name, time) {
+                self.modifier.set_param(name, value).ok();
+            }
+        }
+
+        for slot in self.modifier.slots_mut() {
+            if let Some(cation) = slot.mod_mut() {
+                cation.animate(time);
+            }
+        }
+    }
+
+    pub fn find(&self, id: Uuid) -> Option<&Cation<DynMod>> {
+        self.modifier
+            .slots()
+            .into_iter()
+            .find_map(|slot| slot.find(id))
+    }
+
+    pub fn find_mut(&mut self, id: Uuid) -> Option<&mut Cation<DynMod>> {
+        self.modifier
+            .slots_mut()
+            .into_iter()
+            .find_map(|slot| slot.find_mut(id))
+    }
+
     fn apply(&mut self, input: &Output) -> &Output {
-        let mut copy = input.clone();
-        self.modifier.apply(&mut copy);
-        let output = Output::new(copy.image);
+        let start = Instant::now();
+        let outer = NESTED.with(|nested| nested.replace(Duration::ZERO));
+
+        let output = if self.bypass {
+            input.clone()
+        } else {
+            let mut copy = input.clone();
+            self.modifier.apply(&mut copy);
+            Output::new(copy.image)
+        };
+
+        self.stats.last = start.elapsed();
+        self.stats.total += self.stats.last;
+        let nested = NESTED.with(|nested| nested.replace(outer + self.stats.last));
+        self.stats.last_self = self.stats.last.saturating_sub(nested);
+        self.stats.total_self += self.stats.last_self;
+        self.stats.recomputes += 1;
 
         self.cache = Some(Cache {
             modifier: self.modifier.clone(),
             output,
             input_id: input.id,
+            bypass: self.bypass,
         });
 
         &self.cache.as_ref().unwrap().output
@@ -51,7 +192,7 @@ impl<T: Modifier + Clone + PartialEq> Cation<T> {
 
 impl<T: PartialEq> PartialEq for Cation<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.id == other.id && self.modifier == other.modifier
+        self.id == other.id && self.bypass == other.bypass && self.modifier == other.modifier
     }
 }
 
@@ -79,8 +220,8 @@ impl DynMod {
         }
     }
 
-    pub fn modifier<M: Modifier + Default + 'static>(&self) -> Option<&M> {
-        if self.index == M::index() {
+    pub fn modifier<M: Modifier + 'static>(&self) -> Option<&M> {
+        if self.index.id == TypeId::of::<M>() {
             let ptr: *const _ = &*self.modifier;
             unsafe { Some(&*ptr.cast()) }
         } else {
@@ -88,8 +229,8 @@ impl DynMod {
         }
     }
 
-    pub fn modifier_mut<M: Modifier + Default + 'static>(&mut self) -> Option<&mut M> {
-        if self.index == M::index() {
+    pub fn modifier_mut<M: Modifier + 'static>(&mut self) -> Option<&mut M> {
+        if self.index.id == TypeId::of::<M>() {
             let ptr: *mut _ = &mut *self.modifier;
             unsafe { Some(&mut *ptr.cast()) }
         } else {
@@ -109,9 +250,48 @@ impl Modifier for DynMod {
         self.modifier.apply(input)
     }
 
+    #[cfg(feature = "gui")]
     fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {
         self.modifier.view(ui, editor);
     }
+
+    fn params(&self) -> Vec<Param> {
+        self.modifier.params()
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        self.modifier.get_param(name)
+    }
+
+    /// Clamps numbers to the parameter's range first, for values from files, presets, keyframes
+    /// and plugins that did not come from its widget.
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        let value = match self.params().into_iter().find(|param| param.name == name) {
+            Some(param) => value.clamped(&param.kind),
+            None => value,
+        };
+        self.modifier.set_param(name, value)
+    }
+
+    fn slots(&self) -> Vec<&ModifierSlot> {
+        self.modifier.slots()
+    }
+
+    fn slots_mut(&mut self) -> Vec<&mut ModifierSlot> {
+        self.modifier.slots_mut()
+    }
+
+    fn set_slot_count(&mut self, count: usize) {
+        self.modifier.set_slot_count(count)
+    }
+
+    fn data(&self) -> Option<Arc<[u8]>> {
+        self.modifier.data()
+    }
+
+    fn set_data(&mut self, data: Arc<[u8]>) -> Result<(), &'static str> {
+        self.modifier.set_data(data)
+    }
 }
 
 #[derive(Clone)]
@@ -141,6 +321,7 @@ struct Cache<T> {
     modifier: T,
     pub output: Output,
     input_id: Uuid,
+    bypass: bool,
 }
 
 impl<T: PartialEq> Cache<T> {
@@ -1,33 +1,47 @@
-use eframe::egui::{self, Ui};
-
-use crate::{
-    applied::AppliedValue,
-    editor::Editor,
-    modifier::{cation::Output, traits::Modifier},
+use crate::modifier::{
+    cation::Output,
+    metadata::{Category, Metadata},
+    param::{Param, Value, UNKNOWN, WRONG_TYPE},
+    traits::Modifier,
 };
 
 #[derive(Clone, Default, PartialEq)]
 pub struct Blur {
-    sigma: AppliedValue<f32>,
+    pub sigma: f32,
 }
 
 impl Modifier for Blur {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Filter,
+            "💧",
+            "Blurs the image by a radius.",
+            &["gaussian", "soften", "smooth"],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
         if let Some(image) = &mut input.image {
-            image.blur(*self.sigma);
+            image.blur(self.sigma);
         }
     }
 
-    fn view(&mut self, ui: &mut Ui, _: &mut Editor) {
-        ui.horizontal(|ui| {
-            ui.label("sigma:");
-            self.sigma.view(|value| {
-                ui.add(
-                    egui::DragValue::new(value)
-                        .speed(0.01)
-                        .clamp_range(0.01..=f32::MAX),
-                )
-            });
-        });
+    fn params(&self) -> Vec<Param> {
+        vec![Param::float("sigma", 0.01..=f32::MAX, 0.01)]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "sigma" => Some(Value::Float(self.sigma)),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "sigma" => self.sigma = value.as_f32().ok_or(WRONG_TYPE)?,
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
     }
 }
@@ -1,26 +1,47 @@
-use eframe::egui::{self, Ui};
-
-use crate::{
-    editor::Editor,
-    modifier::{cation::Output, traits::Modifier},
+use crate::modifier::{
+    cation::Output,
+    metadata::{Category, Metadata},
+    param::{Param, Value, UNKNOWN, WRONG_TYPE},
+    traits::Modifier,
 };
 
 #[derive(Clone, Default, PartialEq)]
 pub struct Brighten {
-    value: i32,
+    pub value: i32,
 }
 
 impl Modifier for Brighten {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Adjust,
+            "🔆",
+            "Makes the image lighter or darker.",
+            &["brightness", "lighten", "darken", "exposure"],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
         if let Some(image) = &mut input.image {
             image.brighten(self.value);
         }
     }
 
-    fn view(&mut self, ui: &mut Ui, _: &mut Editor) {
-        ui.horizontal(|ui| {
-            ui.label("amount:");
-            ui.add(egui::DragValue::new(&mut self.value));
-        });
+    fn params(&self) -> Vec<Param> {
+        vec![Param::int("amount", -255..=255)]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "amount" => Some(Value::Int(self.value)),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "amount" => self.value = value.as_i32().ok_or(WRONG_TYPE)?,
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
     }
 }
@@ -1,11 +1,17 @@
+#[cfg(feature = "gui")]
 use eframe::egui::{Context, Ui};
 
 use super::{fill::Fill, magic_wand::MagicWand};
+#[cfg(feature = "gui")]
+use crate::{editor::Editor, view::View};
 use crate::{
-    editor::Editor,
-    modifier::{cation::Output, traits::Modifier},
+    modifier::{
+        cation::Output,
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value},
+        traits::Modifier,
+    },
     slot::ModifierSlot,
-    view::View,
 };
 
 #[derive(Clone, PartialEq)]
@@ -24,6 +30,7 @@ impl Default for Bucket {
     }
 }
 
+#[cfg(feature = "gui")]
 impl Bucket {
     pub fn update(&mut self, ctx: &Context, view: &View) {
         self.wand.update(ctx, view);
@@ -31,10 +38,20 @@ impl Bucket {
 }
 
 impl Modifier for Bucket {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Draw,
+            "🌊",
+            "Fills the area of similar colour around a clicked pixel.",
+            &["flood fill", "paint"],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
         self.wand.apply(input)
     }
 
+    #[cfg(feature = "gui")]
     fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {
         self.wand.input.mod_mut().unwrap().modifier.view(ui, editor);
         self.wand.view_threshold(ui);
@@ -43,4 +60,31 @@ impl Modifier for Bucket {
             self.update(ui.ctx(), &editor.view);
         }
     }
+
+    fn params(&self) -> Vec<Param> {
+        self.wand
+            .params()
+            .into_iter()
+            .map(|param| match param.kind {
+                ParamKind::Slot => Param::new("fill", ParamKind::Slot),
+                _ => param,
+            })
+            .collect()
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        self.wand.get_param(name)
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        self.wand.set_param(name, value)
+    }
+
+    fn slots(&self) -> Vec<&ModifierSlot> {
+        self.wand.slots()
+    }
+
+    fn slots_mut(&mut self) -> Vec<&mut ModifierSlot> {
+        self.wand.slots_mut()
+    }
 }
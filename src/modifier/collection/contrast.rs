@@ -1,26 +1,47 @@
-use eframe::egui::{self, Ui};
-
-use crate::{
-    editor::Editor,
-    modifier::{cation::Output, traits::Modifier},
+use crate::modifier::{
+    cation::Output,
+    metadata::{Category, Metadata},
+    param::{Param, Value, UNKNOWN, WRONG_TYPE},
+    traits::Modifier,
 };
 
 #[derive(Clone, Default, PartialEq)]
 pub struct Contrast {
-    value: f32,
+    pub value: f32,
 }
 
 impl Modifier for Contrast {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Adjust,
+            "◑",
+            "Increases or reduces the difference between light and dark.",
+            &["levels"],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
         if let Some(image) = &mut input.image {
             image.contrast(self.value);
         }
     }
 
-    fn view(&mut self, ui: &mut Ui, _: &mut Editor) {
-        ui.horizontal(|ui| {
-            ui.label("amount:");
-            ui.add(egui::DragValue::new(&mut self.value).clamp_range(-100.0..=f32::MAX));
-        });
+    fn params(&self) -> Vec<Param> {
+        vec![Param::float("amount", -100.0..=f32::MAX, 1.0)]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "amount" => Some(Value::Float(self.value)),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "amount" => self.value = value.as_f32().ok_or(WRONG_TYPE)?,
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,183 @@
+#[cfg(feature = "gui")]
+use eframe::egui::{TextEdit, Ui};
+use image::RgbaImage;
+
+use self::parse::{ParseError, Program, A, B, G, HEIGHT, LUMA, R, R2, WIDTH, X, Y};
+#[cfg(feature = "gui")]
+use crate::editor::Editor;
+use crate::{
+    image::par_pixels_with,
+    modifier::{
+        cation::Output,
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+        traits::Modifier,
+    },
+    slot::ModifierSlot,
+};
+
+pub mod parse;
+
+/// Sets every pixel with formulas like `r = g * 0.5 + sin(x / 10)`. Channels are in `0..=1`, the
+/// second input is read as `r2`, `g2`, `b2` and `a2`.
+#[derive(Clone)]
+pub struct Expression {
+    formula: String,
+    program: Result<Program, ParseError>,
+    pub input: ModifierSlot,
+}
+
+impl Default for Expression {
+    fn default() -> Self {
+        Self::new("r = 1 - r\ng = 1 - g\nb = 1 - b")
+    }
+}
+
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        self.formula == other.formula && self.input == other.input
+    }
+}
+
+impl Expression {
+    pub fn new(formula: &str) -> Self {
+        Self {
+            formula: formula.to_string(),
+            program: parse::parse(formula),
+            input: ModifierSlot::default(),
+        }
+    }
+
+    pub fn formula(&self) -> &str {
+        &self.formula
+    }
+
+    /// Changes and compiles the formula.
+    pub fn set_formula(&mut self, formula: String) {
+        self.program = parse::parse(&formula);
+        self.formula = formula;
+    }
+
+    pub fn error(&self) -> Option<&ParseError> {
+        self.program.as_ref().err()
+    }
+}
+
+impl Modifier for Expression {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Filter,
+            "∑",
+            "Sets every pixel with a formula.",
+            &["formula", "math", "script", "channels"],
+        )
+    }
+
+    fn apply(&mut self, input: &mut Output) {
+        let Ok(program) = &self.program else {
+            return;
+        };
+
+        let second = self
+            .input
+            .mod_mut()
+            .and_then(|wrapped| wrapped.output(input).image.clone());
+
+        if let Some(image) = &mut input.image {
+            run(
+                program,
+                image.as_rgba8_mut(),
+                second.as_ref().map(|image| image.as_rgba8()),
+            );
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {
+        let mut formula = self.formula.clone();
+        if ui
+            .add(
+                TextEdit::multiline(&mut formula)
+                    .code_editor()
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY),
+            )
+            .changed()
+        {
+            self.set_formula(formula);
+        }
+
+        if let Some(error) = self.error() {
+            ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+        }
+
+        ui.weak(format!("variables: {}", parse::INPUTS.join(", ")));
+
+        ui.label("input:");
+        self.input.view_with_frame(ui, editor, 0, None);
+    }
+
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::new("formula", ParamKind::Text),
+            Param::new("input", ParamKind::Slot),
+        ]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "formula" => Some(Value::Text(self.formula.clone())),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "formula" => self.set_formula(value.as_text().ok_or(WRONG_TYPE)?.to_string()),
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
+    }
+
+    fn slots(&self) -> Vec<&ModifierSlot> {
+        vec![&self.input]
+    }
+
+    fn slots_mut(&mut self) -> Vec<&mut ModifierSlot> {
+        vec![&mut self.input]
+    }
+}
+
+/// Runs the program for every pixel.
+fn run(program: &Program, image: &mut RgbaImage, second: Option<&RgbaImage>) {
+    let (width, height) = image.dimensions();
+
+    let registers = || {
+        let mut registers = vec![0.0; program.registers()];
+        registers[WIDTH] = width as f32;
+        registers[HEIGHT] = height as f32;
+        registers
+    };
+
+    par_pixels_with(image, registers, |registers, x, y, pixel| {
+        registers[X] = x as f32;
+        registers[Y] = y as f32;
+        for (channel, value) in pixel.0.iter().enumerate() {
+            registers[R + channel] = *value as f32 / 255.0;
+        }
+        registers[LUMA] = 0.2126 * registers[R] + 0.7152 * registers[G] + 0.0722 * registers[B];
+
+        let other = second
+            .and_then(|second| second.get_pixel_checked(x, y))
+            .map_or([0; 4], |pixel| pixel.0);
+        for (channel, value) in other.iter().enumerate() {
+            registers[R2 + channel] = *value as f32 / 255.0;
+        }
+
+        program.run(registers);
+
+        for (channel, register) in [R, G, B, A].into_iter().enumerate() {
+            pixel[channel] = (registers[register].clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+    });
+}
@@ -0,0 +1,557 @@
+use std::fmt::{self, Display};
+
+/// Variables every formula can read, in the order of their registers.
+pub const INPUTS: [&str; 13] = [
+    "x", "y", "width", "height", "r", "g", "b", "a", "luma", "r2", "g2", "b2", "a2",
+];
+
+/// Registers of the variables in [`INPUTS`].
+pub const X: usize = 0;
+pub const Y: usize = 1;
+pub const WIDTH: usize = 2;
+pub const HEIGHT: usize = 3;
+pub const R: usize = 4;
+pub const G: usize = 5;
+pub const B: usize = 6;
+pub const A: usize = 7;
+pub const LUMA: usize = 8;
+pub const R2: usize = 9;
+
+/// Only the channels, and variables introduced by the formula, can be assigned.
+const ASSIGNABLE: [usize; 4] = [R, G, B, A];
+
+/// Assignments that run in order for every pixel.
+#[derive(Clone, Debug)]
+pub struct Program {
+    statements: Vec<(usize, Expr)>,
+    registers: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Number(f32),
+    Var(usize),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Select(Box<Expr>, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Atan,
+    Atan2,
+    Abs,
+    Sqrt,
+    Exp,
+    Ln,
+    Floor,
+    Ceil,
+    Round,
+    Fract,
+    Min,
+    Max,
+    Pow,
+    Clamp,
+    Mix,
+    Step,
+}
+
+const FUNCTIONS: [(&str, Function, usize); 19] = [
+    ("sin", Function::Sin, 1),
+    ("cos", Function::Cos, 1),
+    ("tan", Function::Tan, 1),
+    ("atan", Function::Atan, 1),
+    ("atan2", Function::Atan2, 2),
+    ("abs", Function::Abs, 1),
+    ("sqrt", Function::Sqrt, 1),
+    ("exp", Function::Exp, 1),
+    ("ln", Function::Ln, 1),
+    ("floor", Function::Floor, 1),
+    ("ceil", Function::Ceil, 1),
+    ("round", Function::Round, 1),
+    ("fract", Function::Fract, 1),
+    ("min", Function::Min, 2),
+    ("max", Function::Max, 2),
+    ("pow", Function::Pow, 2),
+    ("clamp", Function::Clamp, 3),
+    ("mix", Function::Mix, 3),
+    ("step", Function::Step, 2),
+];
+
+const CONSTANTS: [(&str, f32); 2] = [("pi", std::f32::consts::PI), ("e", std::f32::consts::E)];
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f32),
+    Ident(String),
+    Symbol(&'static str),
+    Separator,
+    End,
+}
+
+const SYMBOLS: [&str; 21] = [
+    "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "^", "(", ")", ",", "=", "<", ">",
+    "!", "?", ":",
+];
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Compiles formulas like `r = g * 0.5 + sin(x / 10)`, one assignment per line or separated by
+/// `;`. Everything after a `#` is a comment.
+pub fn parse(source: &str) -> Result<Program, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+        variables: INPUTS.iter().map(|name| name.to_string()).collect(),
+    };
+
+    let mut statements = Vec::new();
+
+    loop {
+        while parser.peek() == &Token::Separator {
+            parser.position += 1;
+        }
+
+        if parser.peek() == &Token::End {
+            break;
+        }
+
+        statements.push(parser.statement()?);
+
+        match parser.peek() {
+            Token::Separator | Token::End => (),
+            _ => return Err(parser.error("expected the end of the line")),
+        }
+    }
+
+    Ok(Program {
+        statements,
+        registers: parser.variables.len(),
+    })
+}
+
+impl Program {
+    pub fn registers(&self) -> usize {
+        self.registers
+    }
+
+    /// Runs the assignments on registers that start with the values of [`INPUTS`].
+    pub fn run(&self, registers: &mut [f32]) {
+        for (target, expr) in &self.statements {
+            registers[*target] = expr.eval(registers);
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, registers: &[f32]) -> f32 {
+        match self {
+            Self::Number(value) => *value,
+            Self::Var(register) => registers[*register],
+            Self::Neg(expr) => -expr.eval(registers),
+            Self::Not(expr) => truth(expr.eval(registers) == 0.0),
+            Self::Binary(op, left, right) => {
+                let (a, b) = (left.eval(registers), right.eval(registers));
+                match op {
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div => a / b,
+                    Op::Rem => a.rem_euclid(b),
+                    Op::Pow => a.powf(b),
+                    Op::Eq => truth(a == b),
+                    Op::Ne => truth(a != b),
+                    Op::Lt => truth(a < b),
+                    Op::Le => truth(a <= b),
+                    Op::Gt => truth(a > b),
+                    Op::Ge => truth(a >= b),
+                    Op::And => truth(a != 0.0 && b != 0.0),
+                    Op::Or => truth(a != 0.0 || b != 0.0),
+                }
+            }
+            Self::Select(condition, then, otherwise) => {
+                if condition.eval(registers) != 0.0 {
+                    then.eval(registers)
+                } else {
+                    otherwise.eval(registers)
+                }
+            }
+            Self::Call(function, args) => {
+                let arg = |index: usize| args[index].eval(registers);
+                match function {
+                    Function::Sin => arg(0).sin(),
+                    Function::Cos => arg(0).cos(),
+                    Function::Tan => arg(0).tan(),
+                    Function::Atan => arg(0).atan(),
+                    Function::Atan2 => arg(0).atan2(arg(1)),
+                    Function::Abs => arg(0).abs(),
+                    Function::Sqrt => arg(0).sqrt(),
+                    Function::Exp => arg(0).exp(),
+                    Function::Ln => arg(0).ln(),
+                    Function::Floor => arg(0).floor(),
+                    Function::Ceil => arg(0).ceil(),
+                    Function::Round => arg(0).round(),
+                    Function::Fract => arg(0).fract(),
+                    Function::Min => arg(0).min(arg(1)),
+                    Function::Max => arg(0).max(arg(1)),
+                    Function::Pow => arg(0).powf(arg(1)),
+                    Function::Clamp => arg(0).clamp(arg(1).min(arg(2)), arg(2).max(arg(1))),
+                    Function::Mix => arg(0) + (arg(1) - arg(0)) * arg(2),
+                    Function::Step => truth(arg(1) >= arg(0)),
+                }
+            }
+        }
+    }
+}
+
+fn truth(value: bool) -> f32 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Tokens with their line and column.
+fn tokenize(source: &str) -> Result<Vec<(Token, usize, usize)>, ParseError> {
+    let mut tokens = Vec::new();
+
+    for (line_index, line) in source.lines().enumerate() {
+        let line_number = line_index + 1;
+        let code = line.split('#').next().unwrap_or_default();
+        let mut rest = code;
+
+        loop {
+            rest = rest.trim_start();
+            let column = code.len() - rest.len() + 1;
+
+            let Some(first) = rest.chars().next() else {
+                break;
+            };
+
+            if first.is_ascii_digit() || first == '.' {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                    .unwrap_or(rest.len());
+                let number = rest[..end].parse().map_err(|_| ParseError {
+                    line: line_number,
+                    column,
+                    message: format!("invalid number `{}`", &rest[..end]),
+                })?;
+                tokens.push((Token::Number(number), line_number, column));
+                rest = &rest[end..];
+            } else if first.is_alphabetic() || first == '_' {
+                let end = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                tokens.push((Token::Ident(rest[..end].to_string()), line_number, column));
+                rest = &rest[end..];
+            } else if first == ';' {
+                tokens.push((Token::Separator, line_number, column));
+                rest = &rest[1..];
+            } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol)) {
+                tokens.push((Token::Symbol(symbol), line_number, column));
+                rest = &rest[symbol.len()..];
+            } else {
+                return Err(ParseError {
+                    line: line_number,
+                    column,
+                    message: format!("unexpected `{first}`"),
+                });
+            }
+        }
+
+        tokens.push((Token::Separator, line_number, code.len() + 1));
+    }
+
+    let (line, column) = tokens
+        .last()
+        .map_or((1, 1), |(_, line, column)| (*line, *column));
+    tokens.push((Token::End, line, column));
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize, usize)>,
+    position: usize,
+    variables: Vec<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].0
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].0.clone();
+        if token != Token::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        let (_, line, column) = &self.tokens[self.position];
+        ParseError {
+            line: *line,
+            column: *column,
+            message: message.to_string(),
+        }
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Token::Symbol(candidate) if *candidate == symbol) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), ParseError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{symbol}`")))
+        }
+    }
+
+    fn statement(&mut self) -> Result<(usize, Expr), ParseError> {
+        let Token::Ident(name) = self.peek().clone() else {
+            return Err(self.error("expected a variable to assign"));
+        };
+        let start = self.position;
+        self.position += 1;
+        self.expect("=")?;
+
+        let expr = self.expr()?;
+
+        let target = match self.variables.iter().position(|variable| *variable == name) {
+            Some(register) if register < INPUTS.len() && !ASSIGNABLE.contains(&register) => {
+                self.position = start;
+                return Err(self.error(&format!("`{name}` can not be assigned")));
+            }
+            Some(register) => register,
+            None => {
+                self.variables.push(name);
+                self.variables.len() - 1
+            }
+        };
+
+        Ok((target, expr))
+    }
+
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        let condition = self.binary(0)?;
+
+        if self.eat("?") {
+            let then = self.expr()?;
+            self.expect(":")?;
+            let otherwise = self.expr()?;
+            Ok(Expr::Select(
+                Box::new(condition),
+                Box::new(then),
+                Box::new(otherwise),
+            ))
+        } else {
+            Ok(condition)
+        }
+    }
+
+    /// Binary operators by precedence, lowest first.
+    fn binary(&mut self, level: usize) -> Result<Expr, ParseError> {
+        const LEVELS: [&[(&str, Op)]; 5] = [
+            &[("||", Op::Or)],
+            &[("&&", Op::And)],
+            &[
+                ("==", Op::Eq),
+                ("!=", Op::Ne),
+                ("<=", Op::Le),
+                (">=", Op::Ge),
+                ("<", Op::Lt),
+                (">", Op::Gt),
+            ],
+            &[("+", Op::Add), ("-", Op::Sub)],
+            &[("*", Op::Mul), ("/", Op::Div), ("%", Op::Rem)],
+        ];
+
+        let Some(ops) = LEVELS.get(level) else {
+            return self.unary();
+        };
+
+        let mut left = self.binary(level + 1)?;
+
+        while let Some((_, op)) = ops.iter().find(|(symbol, _)| self.eat(symbol)) {
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(*op, Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if self.eat("-") {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else if self.eat("!") {
+            Ok(Expr::Not(Box::new(self.unary()?)))
+        } else {
+            let base = self.primary()?;
+            if self.eat("^") {
+                let exponent = self.unary()?;
+                Ok(Expr::Binary(Op::Pow, Box::new(base), Box::new(exponent)))
+            } else {
+                Ok(base)
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let start = self.position;
+
+        match self.next() {
+            Token::Number(value) => Ok(Expr::Number(value)),
+            Token::Symbol("(") => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Ident(name) if self.eat("(") => {
+                let mut args = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        args.push(self.expr()?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+
+                let Some((_, function, arity)) = FUNCTIONS.iter().find(|(n, ..)| *n == name) else {
+                    self.position = start;
+                    return Err(self.error(&format!("unknown function `{name}`")));
+                };
+
+                if args.len() != *arity {
+                    self.position = start;
+                    return Err(self.error(&format!("`{name}` takes {arity} arguments")));
+                }
+
+                Ok(Expr::Call(*function, args))
+            }
+            Token::Ident(name) => {
+                if let Some(register) = self.variables.iter().position(|variable| *variable == name)
+                {
+                    Ok(Expr::Var(register))
+                } else if let Some((_, value)) = CONSTANTS.iter().find(|(n, _)| *n == name) {
+                    Ok(Expr::Number(*value))
+                } else {
+                    self.position = start;
+                    Err(self.error(&format!("unknown variable `{name}`")))
+                }
+            }
+            _ => {
+                self.position = start;
+                Err(self.error("expected a value"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The value the formula assigns to `r`, with every input at zero.
+    fn eval(formula: &str) -> f32 {
+        let program = parse(&format!("r = {formula}")).unwrap();
+        let mut registers = vec![0.0; program.registers()];
+        program.run(&mut registers);
+        registers[R]
+    }
+
+    fn error(source: &str) -> (usize, usize, String) {
+        let err = parse(source).unwrap_err();
+        (err.line, err.column, err.message)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+        assert_eq!(eval("1 - 2 - 3"), -4.0);
+        assert_eq!(eval("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(eval("-2 ^ 2"), -4.0);
+        assert_eq!(eval("7 % 4 * 2"), 6.0);
+        assert_eq!(eval("1 + 1 == 2 && 3 > 2"), 1.0);
+        assert_eq!(eval("0 || 1 && 0"), 0.0);
+        assert_eq!(eval("0 ? 1 : 2 + 3"), 5.0);
+        assert_eq!(eval("max(1, 2) * min(3, 4)"), 6.0);
+    }
+
+    #[test]
+    fn statements() {
+        let program = parse("t = 2 # a comment\nr = t * 3; g = r + 1").unwrap();
+        let mut registers = vec![0.0; program.registers()];
+        program.run(&mut registers);
+        assert_eq!((registers[R], registers[G]), (6.0, 7.0));
+    }
+
+    #[test]
+    fn error_positions() {
+        assert_eq!(error("r = 1 +"), (1, 8, "expected a value".to_string()));
+        assert_eq!(
+            error("r = 1\ng = foo"),
+            (2, 5, "unknown variable `foo`".to_string())
+        );
+        assert_eq!(
+            error("r = 1\n\ng = sin(1, 2)"),
+            (3, 5, "`sin` takes 1 arguments".to_string())
+        );
+        assert_eq!(
+            error("x = 1"),
+            (1, 1, "`x` can not be assigned".to_string())
+        );
+        assert_eq!(error("r = 1 $"), (1, 7, "unexpected `$`".to_string()));
+        assert_eq!(error("r = (1"), (1, 7, "expected `)`".to_string()));
+        assert_eq!(
+            error("r = 1 2"),
+            (1, 7, "expected the end of the line".to_string())
+        );
+    }
+}
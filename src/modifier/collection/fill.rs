@@ -1,36 +1,60 @@
-use eframe::egui::{Color32, Ui};
-
 use crate::{
-    editor::Editor,
-    modifier::{cation::Output, traits::Modifier},
+    color::Color,
+    modifier::{
+        cation::Output,
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+        traits::Modifier,
+    },
 };
 
 #[derive(Clone, PartialEq)]
 pub struct Fill {
-    color: Color32,
+    pub color: Color,
 }
 
 impl Default for Fill {
     fn default() -> Self {
         Self {
-            color: Color32::BLACK,
+            color: Color::BLACK,
         }
     }
 }
 
 impl Modifier for Fill {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Draw,
+            "⬛",
+            "Fills the whole image with a colour.",
+            &["solid", "colour", "color", "background"],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
         if let Some(image) = &mut input.image {
             for position in image.iter_coords() {
-                image.set_pixel(position, self.color.into()).ok();
+                image.set_pixel(position, self.color).ok();
             }
         }
     }
 
-    fn view(&mut self, ui: &mut Ui, _: &mut Editor) {
-        ui.horizontal(|ui| {
-            ui.label("color:");
-            ui.color_edit_button_srgba(&mut self.color);
-        });
+    fn params(&self) -> Vec<Param> {
+        vec![Param::new("color", ParamKind::Color)]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "color" => Some(Value::Color(self.color)),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "color" => self.color = value.as_color().ok_or(WRONG_TYPE)?,
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
     }
 }
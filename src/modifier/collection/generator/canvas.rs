@@ -0,0 +1,57 @@
+use super::Generator;
+use crate::{
+    color::Color,
+    modifier::{
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+    },
+    position::Position,
+};
+
+/// A blank image of one colour.
+#[derive(Clone, PartialEq)]
+pub struct Canvas {
+    pub color: Color,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+        }
+    }
+}
+
+impl Generator for Canvas {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Source,
+            "⬜",
+            "A blank image of one colour.",
+            &["blank", "new", "empty", "solid", "background"],
+        )
+    }
+
+    fn color(&self, _: f32, _: f32, _: Position) -> Color {
+        self.color
+    }
+
+    fn params(&self) -> Vec<Param> {
+        vec![Param::new("color", ParamKind::Color)]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "color" => Some(Value::Color(self.color)),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "color" => self.color = value.as_color().ok_or(WRONG_TYPE)?,
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use std::f32::consts::TAU;
+
+use super::{mix, Generator};
+use crate::{
+    color::Color,
+    modifier::{
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+    },
+    position::Position,
+};
+
+const SHAPES: [&str; 3] = ["linear", "radial", "conic"];
+
+/// Blends between two colours along a line, outwards from a centre or around it.
+#[derive(Clone, PartialEq)]
+pub struct Gradient {
+    /// Index into [`SHAPES`].
+    shape: usize,
+    from: Color,
+    to: Color,
+    /// Direction of a linear gradient and start of a conic one, in degrees.
+    angle: f32,
+    /// In percent of the image size.
+    center: Position,
+    /// Distance at which a radial gradient reaches the second colour, in percent of the width.
+    radius: f32,
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self {
+            shape: 0,
+            from: Color::BLACK,
+            to: Color::WHITE,
+            angle: 0.0,
+            center: Position::new(50.0, 50.0),
+            radius: 50.0,
+        }
+    }
+}
+
+impl Generator for Gradient {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Source,
+            "🌅",
+            "A linear, radial or conic gradient between two colours.",
+            &["gradient", "ramp", "fade", "blend"],
+        )
+    }
+
+    fn color(&self, x: f32, y: f32, size: Position) -> Color {
+        let center = size * (self.center / 100.0);
+        let (dx, dy) = (x - center.x, y - center.y);
+        let angle = self.angle.to_radians();
+
+        let t = match self.shape {
+            // the whole width along the direction, centred on the centre
+            0 => 0.5 + (dx * angle.cos() + dy * angle.sin()) / size.x,
+            1 => (dx * dx + dy * dy).sqrt() / (size.x * self.radius / 100.0),
+            _ => (dy.atan2(dx) - angle).rem_euclid(TAU) / TAU,
+        };
+
+        mix(self.from, self.to, t)
+    }
+
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::new("shape", ParamKind::Enum(&SHAPES)),
+            Param::new("from", ParamKind::Color),
+            Param::new("to", ParamKind::Color),
+            Param::float("angle", -360.0..=360.0, 1.0).suffix("°"),
+            Param::new("center", ParamKind::Position),
+            Param::float("radius", 0.1..=f32::MAX, 0.5).suffix("%"),
+        ]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "shape" => Some(Value::Enum(self.shape)),
+            "from" => Some(Value::Color(self.from)),
+            "to" => Some(Value::Color(self.to)),
+            "angle" => Some(Value::Float(self.angle)),
+            "center" => Some(Value::Position(self.center)),
+            "radius" => Some(Value::Float(self.radius)),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "shape" => {
+                let shape = value.as_enum().ok_or(WRONG_TYPE)?;
+                if shape >= SHAPES.len() {
+                    return Err(WRONG_TYPE);
+                }
+                self.shape = shape;
+            }
+            "from" => self.from = value.as_color().ok_or(WRONG_TYPE)?,
+            "to" => self.to = value.as_color().ok_or(WRONG_TYPE)?,
+            "angle" => self.angle = value.as_f32().ok_or(WRONG_TYPE)?,
+            "center" => self.center = value.as_position().ok_or(WRONG_TYPE)?,
+            "radius" => self.radius = value.as_f32().ok_or(WRONG_TYPE)?.max(0.1),
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use image::{DynamicImage, Rgba, RgbaImage};
+
+use crate::{
+    color::Color,
+    image::{par_pixels, Image},
+    modifier::{
+        cation::Output,
+        metadata::Metadata,
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+        traits::Modifier,
+    },
+    position::Position,
+};
+
+pub mod canvas;
+pub mod gradient;
+pub mod noise;
+pub mod pattern;
+pub mod voronoi;
+
+/// The largest width and height of a generated image.
+pub const MAX_SIZE: u32 = 16384;
+
+/// Creates an image from nothing, one pixel at a time.
+pub trait Generator {
+    fn metadata() -> Metadata
+    where
+        Self: Sized;
+
+    /// The colour of the pixel at `x`, `y` in an image of the given size.
+    fn color(&self, x: f32, y: f32, size: Position) -> Color;
+
+    fn params(&self) -> Vec<Param> {
+        Vec::new()
+    }
+
+    fn get_param(&self, _name: &str) -> Option<Value> {
+        None
+    }
+
+    fn set_param(&mut self, _name: &str, _value: Value) -> Result<(), &'static str> {
+        Err(UNKNOWN)
+    }
+}
+
+/// Replaces the input with a generated image of the given size.
+#[derive(Clone, PartialEq)]
+pub struct GeneratorMod<T> {
+    pub size: Position,
+    pub generator: T,
+}
+
+impl<T: Default> Default for GeneratorMod<T> {
+    fn default() -> Self {
+        Self {
+            size: Position::new(512.0, 512.0),
+            generator: T::default(),
+        }
+    }
+}
+
+impl<T> GeneratorMod<T> {
+    pub fn new(size: Position, generator: T) -> Self {
+        Self { size, generator }
+    }
+}
+
+impl<T: Generator + Default + PartialEq + Clone + Sync + 'static> Modifier for GeneratorMod<T> {
+    fn metadata() -> Metadata {
+        T::metadata()
+    }
+
+    fn apply(&mut self, input: &mut Output) {
+        let (width, height) = self.size.try_into_u32().unwrap_or((1, 1));
+        let mut image = RgbaImage::new(width.clamp(1, MAX_SIZE), height.clamp(1, MAX_SIZE));
+        generate(&self.generator, &mut image);
+        input.image = Some(Image::from_dyn(DynamicImage::ImageRgba8(image)));
+    }
+
+    fn params(&self) -> Vec<Param> {
+        let mut params = vec![Param::new("size", ParamKind::Position)];
+        params.extend(self.generator.params());
+        params
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "size" => Some(Value::Position(self.size)),
+            _ => self.generator.get_param(name),
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "size" => {
+                self.size = value
+                    .as_position()
+                    .ok_or(WRONG_TYPE)?
+                    .max(Position::ONE)
+                    .min(Position::from_u32(MAX_SIZE, MAX_SIZE))
+            }
+            _ => return self.generator.set_param(name, value),
+        }
+        Ok(())
+    }
+}
+
+/// Sets every pixel to the generator's colour.
+fn generate(generator: &(impl Generator + Sync), image: &mut RgbaImage) {
+    let size = Position::from_u32(image.width(), image.height());
+
+    par_pixels(image, |x, y, pixel| {
+        // sampled at the centre of the pixel
+        let color = generator.color(x as f32 + 0.5, y as f32 + 0.5, size);
+        *pixel = Rgba(color.into_rgba_u8());
+    });
+}
+
+/// Blends from `a` at 0 to `b` at 1.
+pub fn mix(a: Color, b: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    Color::from_rgba(
+        a.r + (b.r - a.r) * t,
+        a.g + (b.g - a.g) * t,
+        a.b + (b.b - a.b) * t,
+        a.a + (b.a - a.a) * t,
+    )
+}
+
+/// A pseudo random number in `0..1`, always the same for the same coordinates and seed.
+pub fn hash(x: i32, y: i32, seed: i32) -> f32 {
+    let mut hash = (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ (seed as u32).wrapping_mul(0xcb1a_b31f);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0x5bd1_e995);
+    hash ^= hash >> 15;
+    hash as f32 / u32::MAX as f32
+}
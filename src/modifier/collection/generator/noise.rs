@@ -0,0 +1,205 @@
+use std::f32::consts::{SQRT_2, TAU};
+
+use super::{hash, mix, Generator};
+use crate::{
+    color::Color,
+    modifier::{
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+    },
+    position::Position,
+};
+
+const KINDS: [&str; 4] = ["white", "value", "perlin", "worley"];
+
+/// Random noise blending between two colours, the same for the same seed.
+#[derive(Clone, PartialEq)]
+pub struct Noise {
+    /// Index into [`KINDS`].
+    kind: usize,
+    seed: i32,
+    /// Size of the features, in pixels. White noise is random per pixel.
+    scale: f32,
+    /// Layers of finer value or Perlin noise added on top.
+    octaves: i32,
+    from: Color,
+    to: Color,
+}
+
+impl Default for Noise {
+    fn default() -> Self {
+        Self {
+            kind: 2,
+            seed: 0,
+            scale: 64.0,
+            octaves: 4,
+            from: Color::BLACK,
+            to: Color::WHITE,
+        }
+    }
+}
+
+impl Generator for Noise {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Source,
+            "☁",
+            "White, value, Perlin or Worley noise from a seed.",
+            &["random", "perlin", "worley", "clouds", "grain", "texture"],
+        )
+    }
+
+    fn color(&self, x: f32, y: f32, _: Position) -> Color {
+        let (x, y) = (x / self.scale, y / self.scale);
+
+        let value = match self.kind {
+            0 => hash((x * self.scale) as i32, (y * self.scale) as i32, self.seed),
+            1 => fractal(x, y, self.seed, self.octaves, value),
+            2 => fractal(x, y, self.seed, self.octaves, perlin),
+            _ => nearest(x, y, self.seed).0.min(1.0),
+        };
+
+        mix(self.from, self.to, value)
+    }
+
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::new("kind", ParamKind::Enum(&KINDS)),
+            Param::int("seed", i32::MIN..=i32::MAX),
+            Param::float("scale", 1.0..=f32::MAX, 0.5).suffix("px"),
+            Param::int("octaves", 1..=8),
+            Param::new("from", ParamKind::Color),
+            Param::new("to", ParamKind::Color),
+        ]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "kind" => Some(Value::Enum(self.kind)),
+            "seed" => Some(Value::Int(self.seed)),
+            "scale" => Some(Value::Float(self.scale)),
+            "octaves" => Some(Value::Int(self.octaves)),
+            "from" => Some(Value::Color(self.from)),
+            "to" => Some(Value::Color(self.to)),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "kind" => {
+                let kind = value.as_enum().ok_or(WRONG_TYPE)?;
+                if kind >= KINDS.len() {
+                    return Err(WRONG_TYPE);
+                }
+                self.kind = kind;
+            }
+            "seed" => self.seed = value.as_i32().ok_or(WRONG_TYPE)?,
+            "scale" => self.scale = value.as_f32().ok_or(WRONG_TYPE)?.max(1.0),
+            "octaves" => self.octaves = value.as_i32().ok_or(WRONG_TYPE)?.clamp(1, 8),
+            "from" => self.from = value.as_color().ok_or(WRONG_TYPE)?,
+            "to" => self.to = value.as_color().ok_or(WRONG_TYPE)?,
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Random values at whole coordinates, smoothly blended in between.
+fn value(x: f32, y: f32, seed: i32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (u, v) = (fade(x - x0), fade(y - y0));
+    let (x0, y0) = (x0 as i32, y0 as i32);
+
+    lerp(
+        lerp(hash(x0, y0, seed), hash(x0 + 1, y0, seed), u),
+        lerp(hash(x0, y0 + 1, seed), hash(x0 + 1, y0 + 1, seed), u),
+        v,
+    )
+}
+
+/// Random gradients at whole coordinates, in `0..1`.
+fn perlin(x: f32, y: f32, seed: i32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as i32, y0 as i32);
+
+    let gradient = |ix: i32, iy: i32, dx: f32, dy: f32| {
+        let angle = hash(ix, iy, seed) * TAU;
+        angle.cos() * dx + angle.sin() * dy
+    };
+
+    let (u, v) = (fade(fx), fade(fy));
+    let noise = lerp(
+        lerp(
+            gradient(x0, y0, fx, fy),
+            gradient(x0 + 1, y0, fx - 1.0, fy),
+            u,
+        ),
+        lerp(
+            gradient(x0, y0 + 1, fx, fy - 1.0),
+            gradient(x0 + 1, y0 + 1, fx - 1.0, fy - 1.0),
+            u,
+        ),
+        v,
+    );
+
+    // gradient noise lies within plus or minus half the diagonal of a cell
+    noise / SQRT_2 + 0.5
+}
+
+/// Adds octaves of the noise at twice the frequency and half the strength of the previous one.
+fn fractal(x: f32, y: f32, seed: i32, octaves: i32, noise: fn(f32, f32, i32) -> f32) -> f32 {
+    let mut total = 0.0;
+    let mut strength = 1.0;
+    let mut frequency = 1.0;
+    let mut sum = 0.0;
+
+    for octave in 0..octaves {
+        total += noise(x * frequency, y * frequency, seed.wrapping_add(octave)) * strength;
+        sum += strength;
+        strength /= 2.0;
+        frequency *= 2.0;
+    }
+
+    total / sum
+}
+
+/// The random point in the cell at the coordinates.
+pub fn feature(x: i32, y: i32, seed: i32) -> (f32, f32) {
+    (
+        x as f32 + hash(x, y, seed),
+        y as f32 + hash(x, y, seed.wrapping_add(1)),
+    )
+}
+
+/// The distance to the nearest and second nearest feature point, and the cell of the nearest.
+pub fn nearest(x: f32, y: f32, seed: i32) -> (f32, f32, (i32, i32)) {
+    let (cx, cy) = (x.floor() as i32, y.floor() as i32);
+    let mut first = (f32::MAX, (cx, cy));
+    let mut second = f32::MAX;
+
+    for ny in cy - 1..=cy + 1 {
+        for nx in cx - 1..=cx + 1 {
+            let (fx, fy) = feature(nx, ny, seed);
+            let distance = ((fx - x).powi(2) + (fy - y).powi(2)).sqrt();
+
+            if distance < first.0 {
+                second = first.0;
+                first = (distance, (nx, ny));
+            } else if distance < second {
+                second = distance;
+            }
+        }
+    }
+
+    (first.0, second, first.1)
+}
@@ -0,0 +1,117 @@
+use super::Generator;
+use crate::{
+    color::Color,
+    modifier::{
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+    },
+    position::Position,
+};
+
+const KINDS: [&str; 3] = ["checkerboard", "stripes", "grid"];
+
+/// Repeating checkerboard, stripes or grid lines in two colours.
+#[derive(Clone, PartialEq)]
+pub struct Pattern {
+    /// Index into [`KINDS`].
+    kind: usize,
+    from: Color,
+    to: Color,
+    /// Size of a square, a pair of stripes or a grid cell, in pixels.
+    cell: f32,
+    /// Width of the grid lines, in pixels.
+    line: f32,
+    /// Direction of the stripes, in degrees.
+    angle: f32,
+}
+
+impl Default for Pattern {
+    fn default() -> Self {
+        Self {
+            kind: 0,
+            from: Color::WHITE,
+            to: Color::from_rgba(0.8, 0.8, 0.8, 1.0),
+            cell: 32.0,
+            line: 2.0,
+            angle: 45.0,
+        }
+    }
+}
+
+impl Generator for Pattern {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Source,
+            "▦",
+            "A checkerboard, stripes or grid in two colours.",
+            &[
+                "checkerboard",
+                "checker",
+                "stripes",
+                "grid",
+                "lines",
+                "tiles",
+            ],
+        )
+    }
+
+    fn color(&self, x: f32, y: f32, _: Position) -> Color {
+        let second = match self.kind {
+            0 => ((x / self.cell).floor() + (y / self.cell).floor()) as i64 % 2 != 0,
+            1 => {
+                let angle = self.angle.to_radians();
+                let across = x * angle.sin() - y * angle.cos();
+                (across / self.cell * 2.0).floor() as i64 % 2 != 0
+            }
+            _ => x.rem_euclid(self.cell) < self.line || y.rem_euclid(self.cell) < self.line,
+        };
+
+        if second {
+            self.to
+        } else {
+            self.from
+        }
+    }
+
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::new("kind", ParamKind::Enum(&KINDS)),
+            Param::new("from", ParamKind::Color),
+            Param::new("to", ParamKind::Color),
+            Param::float("cell", 1.0..=f32::MAX, 0.5).suffix("px"),
+            Param::float("line", 0.0..=f32::MAX, 0.1).suffix("px"),
+            Param::float("angle", -360.0..=360.0, 1.0).suffix("°"),
+        ]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "kind" => Some(Value::Enum(self.kind)),
+            "from" => Some(Value::Color(self.from)),
+            "to" => Some(Value::Color(self.to)),
+            "cell" => Some(Value::Float(self.cell)),
+            "line" => Some(Value::Float(self.line)),
+            "angle" => Some(Value::Float(self.angle)),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "kind" => {
+                let kind = value.as_enum().ok_or(WRONG_TYPE)?;
+                if kind >= KINDS.len() {
+                    return Err(WRONG_TYPE);
+                }
+                self.kind = kind;
+            }
+            "from" => self.from = value.as_color().ok_or(WRONG_TYPE)?,
+            "to" => self.to = value.as_color().ok_or(WRONG_TYPE)?,
+            "cell" => self.cell = value.as_f32().ok_or(WRONG_TYPE)?.max(1.0),
+            "line" => self.line = value.as_f32().ok_or(WRONG_TYPE)?.max(0.0),
+            "angle" => self.angle = value.as_f32().ok_or(WRONG_TYPE)?,
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use super::{hash, noise::nearest, Generator};
+use crate::{
+    color::Color,
+    modifier::{
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+    },
+    position::Position,
+};
+
+/// Cells around random points, each in a random colour.
+#[derive(Clone, PartialEq)]
+pub struct Voronoi {
+    seed: i32,
+    /// Average size of a cell, in pixels.
+    scale: f32,
+    /// Width of the lines between cells, in pixels.
+    border: f32,
+    border_color: Color,
+}
+
+impl Default for Voronoi {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            scale: 64.0,
+            border: 0.0,
+            border_color: Color::BLACK,
+        }
+    }
+}
+
+impl Generator for Voronoi {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Source,
+            "▩",
+            "Cells around random points, each in a random colour.",
+            &["voronoi", "cells", "mosaic", "stained glass", "random"],
+        )
+    }
+
+    fn color(&self, x: f32, y: f32, _: Position) -> Color {
+        let (first, second, (cx, cy)) = nearest(x / self.scale, y / self.scale, self.seed);
+
+        // the gap to the second nearest point is about twice the distance to the edge
+        if (second - first) * self.scale / 2.0 < self.border {
+            return self.border_color;
+        }
+
+        Color::from_hsv(
+            hash(cx, cy, self.seed.wrapping_add(2)) * std::f32::consts::TAU,
+            0.4 + hash(cx, cy, self.seed.wrapping_add(3)) * 0.4,
+            0.6 + hash(cx, cy, self.seed.wrapping_add(4)) * 0.4,
+        )
+    }
+
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::int("seed", i32::MIN..=i32::MAX),
+            Param::float("scale", 1.0..=f32::MAX, 0.5).suffix("px"),
+            Param::float("border", 0.0..=f32::MAX, 0.1).suffix("px"),
+            Param::new("border color", ParamKind::Color),
+        ]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "seed" => Some(Value::Int(self.seed)),
+            "scale" => Some(Value::Float(self.scale)),
+            "border" => Some(Value::Float(self.border)),
+            "border color" => Some(Value::Color(self.border_color)),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "seed" => self.seed = value.as_i32().ok_or(WRONG_TYPE)?,
+            "scale" => self.scale = value.as_f32().ok_or(WRONG_TYPE)?.max(1.0),
+            "border" => self.border = value.as_f32().ok_or(WRONG_TYPE)?.max(0.0),
+            "border color" => self.border_color = value.as_color().ok_or(WRONG_TYPE)?,
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,732 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[cfg(feature = "gui")]
+use eframe::{
+    egui::{self, Align2, Color32, Context, Id, Rect, Sense, Stroke, TextStyle, Ui, Vec2},
+    epaint::CubicBezierShape,
+};
+use uuid::Uuid;
+
+#[cfg(feature = "gui")]
+use crate::{clipboard, editor::Editor};
+use crate::{
+    modifier::{
+        cation::Output,
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+        traits::Modifier,
+    },
+    position::Position,
+    slot::ModifierSlot,
+};
+
+/// The port every node has for the image it modifies.
+pub const MAIN_PORT: &str = "in";
+
+/// Modifiers wired as a graph, where one output can feed any number of inputs. A node modifies
+/// the output of the node connected to its [`MAIN_PORT`], or the graph's input, and each of its
+/// slot parameters is another port. A [`List`](super::list::List) is the special case of a chain.
+#[derive(Clone, Default)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    /// The node whose output is the graph's output, the last node if not set.
+    pub output: Option<Uuid>,
+    #[cfg(feature = "gui")]
+    open: bool,
+    #[cfg(feature = "gui")]
+    connecting: Option<Uuid>,
+}
+
+#[derive(Clone)]
+pub struct GraphNode {
+    pub id: Uuid,
+    pub slot: ModifierSlot,
+    /// The source node of every connected port.
+    pub inputs: BTreeMap<String, Uuid>,
+    /// Where the node is drawn in the graph editor.
+    pub position: Position,
+    /// Kept between applies so connected slots can use their cache.
+    links: BTreeMap<String, ModifierSlot>,
+}
+
+/// Passes on the output of another node. Swapped into a connected slot only while the graph
+/// applies, so it is never saved.
+#[derive(Clone, Default)]
+struct Link {
+    output: Option<Output>,
+}
+
+impl PartialEq for Graph {
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes && self.output == other.output
+    }
+}
+
+impl PartialEq for GraphNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.slot == other.slot && self.inputs == other.inputs
+    }
+}
+
+impl PartialEq for Link {
+    fn eq(&self, other: &Self) -> bool {
+        self.output.as_ref().map(|output| output.id)
+            == other.output.as_ref().map(|output| output.id)
+    }
+}
+
+impl Modifier for Link {
+    fn apply(&mut self, input: &mut Output) {
+        if let Some(output) = &self.output {
+            input.image = output.image.clone();
+        }
+    }
+}
+
+impl GraphNode {
+    fn new(slot: ModifierSlot, position: Position) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            slot,
+            inputs: BTreeMap::new(),
+            position,
+            links: BTreeMap::new(),
+        }
+    }
+
+    /// The main port followed by the slot parameters of the modifier.
+    pub fn ports(&self) -> Vec<&'static str> {
+        let slots = self.slot.mod_ref().map_or(Vec::new(), |cation| {
+            cation
+                .modifier
+                .params()
+                .into_iter()
+                .filter(|param| param.kind == ParamKind::Slot)
+                .map(|param| param.name)
+                .collect()
+        });
+
+        std::iter::once(MAIN_PORT).chain(slots).collect()
+    }
+}
+
+impl Graph {
+    /// Adds a node after the current output, which it becomes.
+    pub fn add(&mut self, slot: ModifierSlot) -> Uuid {
+        let previous = self.output_id();
+        let position = previous
+            .and_then(|id| self.node(id))
+            .map_or(Position::new(20.0, 20.0), |node| {
+                node.position + Position::new(180.0, 0.0)
+            });
+
+        let mut node = GraphNode::new(slot, position);
+        if let Some(previous) = previous {
+            node.inputs.insert(MAIN_PORT.to_string(), previous);
+        }
+
+        let id = node.id;
+        self.nodes.push(node);
+        self.output = Some(id);
+        id
+    }
+
+    pub fn remove(&mut self, id: Uuid) {
+        self.nodes.retain(|node| node.id != id);
+        for node in &mut self.nodes {
+            node.inputs.retain(|_, source| *source != id);
+        }
+
+        if self.output == Some(id) {
+            self.output = None;
+        }
+    }
+
+    pub fn node(&self, id: Uuid) -> Option<&GraphNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    fn node_mut(&mut self, id: Uuid) -> Option<&mut GraphNode> {
+        self.nodes.iter_mut().find(|node| node.id == id)
+    }
+
+    pub fn output_id(&self) -> Option<Uuid> {
+        self.output
+            .filter(|id| self.node(*id).is_some())
+            .or_else(|| self.nodes.last().map(|node| node.id))
+    }
+
+    /// Feeds the output of `source` into a port of `target`, unless that would make a cycle.
+    pub fn connect(&mut self, target: Uuid, port: &str, source: Uuid) -> Result<(), &'static str> {
+        if self.dependents(target).contains(&source) {
+            return Err("connection would make a cycle");
+        }
+
+        let node = self.node_mut(target).ok_or("no such node")?;
+        if !node.ports().contains(&port) {
+            return Err("no such port");
+        }
+
+        node.inputs.insert(port.to_string(), source);
+        Ok(())
+    }
+
+    pub fn disconnect(&mut self, target: Uuid, port: &str) {
+        if let Some(node) = self.node_mut(target) {
+            node.inputs.remove(port);
+        }
+    }
+
+    /// Whether the output of `node` is computed from that of `other`.
+    pub fn depends_on(&self, node: Uuid, other: Uuid) -> bool {
+        node != other && self.order(node).contains(&other)
+    }
+
+    /// The nodes whose output is computed from that of `node`, including itself.
+    fn dependents(&self, node: Uuid) -> HashSet<Uuid> {
+        let mut targets = HashMap::<Uuid, Vec<Uuid>>::new();
+        for target in &self.nodes {
+            for source in target.inputs.values() {
+                targets.entry(*source).or_default().push(target.id);
+            }
+        }
+
+        let mut visited = HashSet::new();
+        let mut pending = vec![node];
+        while let Some(id) = pending.pop() {
+            if visited.insert(id) {
+                pending.extend(targets.get(&id).into_iter().flatten());
+            }
+        }
+        visited
+    }
+
+    /// The nodes the output is computed from, each after its inputs.
+    fn order(&self, output: Uuid) -> Vec<Uuid> {
+        fn visit(graph: &Graph, id: Uuid, visited: &mut HashSet<Uuid>, order: &mut Vec<Uuid>) {
+            if !visited.insert(id) {
+                return;
+            }
+
+            if let Some(node) = graph.node(id) {
+                for source in node.inputs.values() {
+                    visit(graph, *source, visited, order);
+                }
+                order.push(id);
+            }
+        }
+
+        let mut order = Vec::new();
+        visit(self, output, &mut HashSet::new(), &mut order);
+        order
+    }
+
+    fn index_of(&self, id: Uuid) -> Option<usize> {
+        self.nodes.iter().position(|node| node.id == id)
+    }
+
+    #[cfg(feature = "gui")]
+    fn label(&self, id: Uuid) -> String {
+        match (
+            self.index_of(id),
+            self.node(id).and_then(|node| node.slot.mod_ref()),
+        ) {
+            (Some(index), Some(cation)) => format!("#{index} {}", cation.modifier.index.name),
+            (Some(index), None) => format!("#{index}"),
+            _ => "(none)".to_string(),
+        }
+    }
+}
+
+impl Modifier for Graph {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Composite,
+            "🔀",
+            "Combines modifiers as connected nodes.",
+            &["nodes", "blend", "mix", "combine"],
+        )
+    }
+
+    fn apply(&mut self, input: &mut Output) {
+        let Some(output) = self.output_id() else {
+            return;
+        };
+
+        let mut outputs: HashMap<Uuid, Output> = HashMap::new();
+
+        for id in self.order(output) {
+            let node = self.node_mut(id).unwrap();
+            let main = node
+                .inputs
+                .get(MAIN_PORT)
+                .and_then(|source| outputs.get(source))
+                .unwrap_or(input);
+
+            let Some(cation) = node.slot.mod_mut() else {
+                outputs.insert(id, main.clone());
+                continue;
+            };
+
+            let mut swapped = Vec::new();
+            for (port, source) in &node.inputs {
+                let Some(source) = outputs.get(source).filter(|_| port != MAIN_PORT) else {
+                    continue;
+                };
+                let Some(slot) = cation.modifier.slot_mut(port) else {
+                    continue;
+                };
+
+                let link = node
+                    .links
+                    .entry(port.clone())
+                    .or_insert_with(|| ModifierSlot::from_mod(Link::default()));
+                if let Some(link) = link
+                    .mod_mut()
+                    .and_then(|link| link.modifier.modifier_mut::<Link>())
+                {
+                    if link.output.as_ref().map(|output| output.id) != Some(source.id) {
+                        link.output = Some(source.clone());
+                    }
+                }
+
+                std::mem::swap(slot, link);
+                swapped.push(port.clone());
+            }
+
+            let result = cation.output(main).clone();
+
+            for port in swapped {
+                if let (Some(slot), Some(link)) =
+                    (cation.modifier.slot_mut(&port), node.links.get_mut(&port))
+                {
+                    std::mem::swap(slot, link);
+                }
+            }
+
+            outputs.insert(id, result);
+        }
+
+        if let Some(result) = outputs.remove(&output) {
+            *input = result;
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {
+        ui.horizontal(|ui| {
+            let mut slot = ModifierSlot::Empty;
+            slot.add_mod_widget(ui, editor, None);
+            if let ModifierSlot::Modifier(_) = slot {
+                editor.try_select_slot(&slot).ok();
+                self.add(slot);
+            }
+
+            if let Some(pasted) = clipboard::paste_button(ui, editor) {
+                self.add(pasted);
+            }
+
+            ui.toggle_value(&mut self.open, "graph editor");
+        });
+
+        if self.nodes.is_empty() {
+            ui.label("(empty)");
+        }
+
+        let mut removed = None;
+
+        for index in 0..self.nodes.len() {
+            let id = self.nodes[index].id;
+
+            ui.push_id(id, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    if ui.radio(self.output_id() == Some(id), "output").clicked() {
+                        self.output = Some(id);
+                    }
+
+                    for port in self.nodes[index].ports() {
+                        self.port_widget(ui, id, port);
+                    }
+
+                    if ui.small_button("🗑").clicked() {
+                        removed = Some(id);
+                    }
+                });
+
+                self.nodes[index].slot.view_with_frame(
+                    ui,
+                    editor,
+                    index,
+                    Some(&format!("#{index}")),
+                );
+            });
+        }
+
+        if let Some(id) = removed {
+            self.remove(id);
+        }
+
+        self.insert_requested(editor);
+
+        if self.open {
+            self.view_editor(ui.ctx());
+        }
+    }
+
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::int("output", -1..=i32::MAX),
+            Param::new("links", ParamKind::List),
+            Param::new("positions", ParamKind::Points),
+        ]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "output" => Some(Value::Int(
+                self.output
+                    .and_then(|id| self.index_of(id))
+                    .map_or(-1, |index| index as i32),
+            )),
+            "links" => Some(Value::List(
+                self.nodes
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(target, node)| {
+                        node.inputs.iter().filter_map(move |(port, source)| {
+                            Some(Value::List(vec![
+                                Value::Int(target as i32),
+                                Value::Text(port.clone()),
+                                Value::Int(self.index_of(*source)? as i32),
+                            ]))
+                        })
+                    })
+                    .collect(),
+            )),
+            "positions" => Some(Value::Points(
+                self.nodes.iter().map(|node| node.position).collect(),
+            )),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "output" => {
+                let index = value.as_i32().ok_or(WRONG_TYPE)?;
+                self.output = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| self.nodes.get(index))
+                    .map(|node| node.id);
+            }
+            "links" => {
+                let links = value.into_list().ok_or(WRONG_TYPE)?;
+
+                for node in &mut self.nodes {
+                    node.inputs.clear();
+                }
+
+                for link in links {
+                    let link = link.into_list().ok_or(WRONG_TYPE)?;
+                    let [target, port, source] = link.as_slice() else {
+                        return Err(WRONG_TYPE);
+                    };
+                    let id = |value: &Value| {
+                        let index = usize::try_from(value.as_i32()?).ok()?;
+                        Some(self.nodes.get(index)?.id)
+                    };
+                    let (Some(target), Some(port), Some(source)) =
+                        (id(target), port.as_text(), id(source))
+                    else {
+                        return Err(WRONG_TYPE);
+                    };
+
+                    self.connect(target, port, source)?;
+                }
+            }
+            "positions" => {
+                let positions = value.into_points().ok_or(WRONG_TYPE)?;
+                for (node, position) in self.nodes.iter_mut().zip(positions) {
+                    node.position = position;
+                }
+            }
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
+    }
+
+    fn slots(&self) -> Vec<&ModifierSlot> {
+        self.nodes.iter().map(|node| &node.slot).collect()
+    }
+
+    fn slots_mut(&mut self) -> Vec<&mut ModifierSlot> {
+        self.nodes.iter_mut().map(|node| &mut node.slot).collect()
+    }
+
+    fn set_slot_count(&mut self, count: usize) {
+        while self.nodes.len() < count {
+            let position = Position::new(20.0 + 180.0 * self.nodes.len() as f32, 20.0);
+            self.nodes
+                .push(GraphNode::new(ModifierSlot::Empty, position));
+        }
+        self.nodes.truncate(count);
+    }
+}
+
+#[cfg(feature = "gui")]
+const NODE_WIDTH: f32 = 150.0;
+#[cfg(feature = "gui")]
+const HEADER_HEIGHT: f32 = 24.0;
+#[cfg(feature = "gui")]
+const PORT_HEIGHT: f32 = 18.0;
+#[cfg(feature = "gui")]
+const PORT_RADIUS: f32 = 5.0;
+
+#[cfg(feature = "gui")]
+impl Graph {
+    /// Adds the copy requested in the editor next to the node it was made from, with the same
+    /// inputs.
+    fn insert_requested(&mut self, editor: &mut Editor) {
+        let Some(index) = editor
+            .insert
+            .as_ref()
+            .and_then(|(after, _)| self.index_of_slot(*after))
+        else {
+            return;
+        };
+
+        let (_, cation) = editor.insert.take().unwrap();
+        editor.select_cation(&cation);
+
+        let anchor = &self.nodes[index];
+        let mut node = GraphNode::new(
+            ModifierSlot::from_cacher(cation),
+            anchor.position + Position::new(0.0, 80.0),
+        );
+        node.inputs = anchor.inputs.clone();
+        self.nodes.insert(index + 1, node);
+    }
+
+    fn index_of_slot(&self, id: Uuid) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|node| node.slot.mod_ref().is_some_and(|cation| cation.id == id))
+    }
+
+    /// Picks the source of a port.
+    fn port_widget(&mut self, ui: &mut Ui, target: Uuid, port: &'static str) {
+        let source = self
+            .node(target)
+            .and_then(|node| node.inputs.get(port).copied());
+        let unconnected = if port == MAIN_PORT {
+            "graph input"
+        } else {
+            "slot"
+        };
+        let selected = source.map_or(unconnected.to_string(), |source| self.label(source));
+
+        egui::ComboBox::from_id_source(port)
+            .selected_text(format!("{port}: {selected}"))
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(source.is_none(), unconnected).clicked() {
+                    self.disconnect(target, port);
+                }
+
+                let dependents = self.dependents(target);
+                let candidates = self
+                    .nodes
+                    .iter()
+                    .map(|node| node.id)
+                    .filter(|id| !dependents.contains(id))
+                    .collect::<Vec<_>>();
+
+                for candidate in candidates {
+                    if ui
+                        .selectable_label(source == Some(candidate), self.label(candidate))
+                        .clicked()
+                    {
+                        self.connect(target, port, candidate).ok();
+                    }
+                }
+            });
+    }
+
+    fn node_rect(&self, node: &GraphNode, origin: Vec2) -> Rect {
+        let height = HEADER_HEIGHT + PORT_HEIGHT * node.ports().len() as f32;
+        Rect::from_min_size(
+            egui::pos2(node.position.x, node.position.y) + origin,
+            Vec2::new(NODE_WIDTH, height),
+        )
+    }
+
+    fn output_port(&self, node: &GraphNode, origin: Vec2) -> egui::Pos2 {
+        self.node_rect(node, origin).right_top() + Vec2::new(0.0, HEADER_HEIGHT / 2.0)
+    }
+
+    fn input_port(&self, node: &GraphNode, index: usize, origin: Vec2) -> egui::Pos2 {
+        self.node_rect(node, origin).left_top()
+            + Vec2::new(0.0, HEADER_HEIGHT + PORT_HEIGHT * (index as f32 + 0.5))
+    }
+
+    /// Window with the nodes as boxes that can be moved, and wired by dragging from an output to
+    /// an input. Right clicking an input disconnects it.
+    fn view_editor(&mut self, ctx: &Context) {
+        let mut open = self.open;
+
+        egui::Window::new("graph")
+            .open(&mut open)
+            .default_size([640.0, 360.0])
+            .show(ctx, |ui| {
+                let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::hover());
+                let origin = response.rect.min.to_vec2();
+                let visuals = ui.visuals().clone();
+                let wire = Stroke::new(2.0f32, visuals.widgets.active.fg_stroke.color);
+                let pointer = ui.ctx().pointer_interact_pos();
+                let released = ui.input(|input| input.pointer.any_released());
+
+                let curve = |from: egui::Pos2, to: egui::Pos2| {
+                    let bend = Vec2::new(((to.x - from.x) / 2.0).abs().max(30.0), 0.0);
+                    CubicBezierShape::from_points_stroke(
+                        [from, from + bend, to - bend, to],
+                        false,
+                        Color32::TRANSPARENT,
+                        wire,
+                    )
+                };
+
+                for node in &self.nodes {
+                    for (index, port) in node.ports().into_iter().enumerate() {
+                        if let Some(source) = node.inputs.get(port).and_then(|id| self.node(*id)) {
+                            painter.add(curve(
+                                self.output_port(source, origin),
+                                self.input_port(node, index, origin),
+                            ));
+                        }
+                    }
+                }
+
+                let output = self.output_id();
+                let mut moved = Vec::new();
+                let mut connect = None;
+                let mut disconnect = None;
+
+                for node in &self.nodes {
+                    let rect = self.node_rect(node, origin);
+                    let header =
+                        Rect::from_min_size(rect.min, Vec2::new(rect.width(), HEADER_HEIGHT));
+
+                    let drag =
+                        ui.interact(header, Id::new(node.id).with("graph node"), Sense::drag());
+                    if drag.dragged() {
+                        moved.push((node.id, drag.drag_delta()));
+                    }
+
+                    let stroke = if output == Some(node.id) {
+                        Stroke::new(2.0f32, visuals.selection.stroke.color)
+                    } else {
+                        visuals.window_stroke()
+                    };
+                    painter.rect(rect, 4.0, visuals.window_fill(), stroke);
+                    painter.text(
+                        header.left_center() + Vec2::new(6.0, 0.0),
+                        Align2::LEFT_CENTER,
+                        self.label(node.id),
+                        TextStyle::Button.resolve(ui.style()),
+                        visuals.strong_text_color(),
+                    );
+
+                    let output_port = self.output_port(node, origin);
+                    painter.circle_filled(output_port, PORT_RADIUS, wire.color);
+                    let output_rect =
+                        Rect::from_center_size(output_port, Vec2::splat(PORT_RADIUS * 3.0));
+                    if ui
+                        .interact(
+                            output_rect,
+                            Id::new(node.id).with("graph output"),
+                            Sense::drag(),
+                        )
+                        .drag_started()
+                    {
+                        self.connecting = Some(node.id);
+                    }
+
+                    for (index, port) in node.ports().into_iter().enumerate() {
+                        let position = self.input_port(node, index, origin);
+                        painter.circle_filled(position, PORT_RADIUS, visuals.text_color());
+                        painter.text(
+                            position + Vec2::new(PORT_RADIUS * 2.0, 0.0),
+                            Align2::LEFT_CENTER,
+                            port,
+                            TextStyle::Small.resolve(ui.style()),
+                            visuals.text_color(),
+                        );
+
+                        let port_rect =
+                            Rect::from_center_size(position, Vec2::splat(PORT_RADIUS * 3.0));
+                        let port_response =
+                            ui.interact(port_rect, Id::new(node.id).with(port), Sense::click());
+                        if port_response.secondary_clicked() {
+                            disconnect = Some((node.id, port));
+                        }
+
+                        if let (Some(source), Some(pointer)) = (self.connecting, pointer) {
+                            if released && port_rect.contains(pointer) {
+                                connect = Some((node.id, port, source));
+                            }
+                        }
+                    }
+                }
+
+                if let (Some(source), Some(pointer)) =
+                    (self.connecting.and_then(|id| self.node(id)), pointer)
+                {
+                    painter.add(curve(self.output_port(source, origin), pointer));
+                }
+
+                for (id, delta) in moved {
+                    if let Some(node) = self.node_mut(id) {
+                        node.position += Position::from(delta);
+                    }
+                }
+
+                if let Some((target, port, source)) = connect {
+                    self.connect(target, port, source).ok();
+                }
+
+                if let Some((target, port)) = disconnect {
+                    self.disconnect(target, port);
+                }
+
+                if released {
+                    self.connecting = None;
+                }
+            });
+
+        self.open = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modifier::collection::overlay::Overlay;
+
+    #[test]
+    fn chained_diamonds_are_not_a_cycle_until_closed() {
+        let mut graph = Graph::default();
+        let first = graph.add(ModifierSlot::from_mod(Overlay::default()));
+
+        // every node reads the previous one twice, doubling the paths back to the first
+        let mut last = first;
+        for _ in 0..40 {
+            let node = graph.add(ModifierSlot::from_mod(Overlay::default()));
+            graph.connect(node, MAIN_PORT, last).unwrap();
+            graph.connect(node, "input", last).unwrap();
+            last = node;
+        }
+
+        assert!(graph.depends_on(last, first));
+        assert!(!graph.depends_on(first, last));
+        assert!(graph.connect(first, MAIN_PORT, last).is_err());
+    }
+}
@@ -1,9 +1,22 @@
-use crate::modifier::{cation::Output, traits::Modifier};
+use crate::modifier::{
+    cation::Output,
+    metadata::{Category, Metadata},
+    traits::Modifier,
+};
 
 #[derive(Clone, Default, PartialEq)]
 pub struct GrayScaleFilter;
 
 impl Modifier for GrayScaleFilter {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Adjust,
+            "◐",
+            "Removes the colour, keeping the brightness.",
+            &["grey", "desaturate", "black and white", "monochrome"],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
         if let Some(image) = &mut input.image {
             image.grayscale();
@@ -1,30 +1,47 @@
-use eframe::egui::{self, Ui};
-
-use crate::{
-    editor::Editor,
-    modifier::{cation::Output, traits::Modifier},
+use crate::modifier::{
+    cation::Output,
+    metadata::{Category, Metadata},
+    param::{Param, Value, UNKNOWN, WRONG_TYPE},
+    traits::Modifier,
 };
 
 #[derive(Clone, Default, PartialEq)]
 pub struct Hue {
-    degrees: i32,
+    pub degrees: i32,
 }
 
 impl Modifier for Hue {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Adjust,
+            "🌈",
+            "Rotates every colour around the colour wheel.",
+            &["colour", "color", "shift", "tint"],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
         if let Some(image) = &mut input.image {
             image.huerotate(self.degrees);
         }
     }
 
-    fn view(&mut self, ui: &mut Ui, _: &mut Editor) {
-        ui.horizontal(|ui| {
-            ui.label("degrees:");
-            ui.add(
-                egui::DragValue::new(&mut self.degrees)
-                    .clamp_range(0..=360)
-                    .suffix("°"),
-            );
-        });
+    fn params(&self) -> Vec<Param> {
+        vec![Param::int("degrees", 0..=360).suffix("°")]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "degrees" => Some(Value::Int(self.degrees)),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "degrees" => self.degrees = value.as_i32().ok_or(WRONG_TYPE)?,
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
     }
 }
@@ -1,9 +1,22 @@
-use crate::modifier::{cation::Output, traits::Modifier};
+use crate::modifier::{
+    cation::Output,
+    metadata::{Category, Metadata},
+    traits::Modifier,
+};
 
 #[derive(Clone, Default, PartialEq)]
 pub struct Invert;
 
 impl Modifier for Invert {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Adjust,
+            "⇄",
+            "Turns the image into its negative.",
+            &["negative", "reverse"],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
         if let Some(image) = &mut input.image {
             image.invert();
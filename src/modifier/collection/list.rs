@@ -1,11 +1,16 @@
+use std::collections::HashSet;
+
+#[cfg(feature = "gui")]
 use eframe::egui::{self, Ui};
 use uuid::Uuid;
 
+#[cfg(feature = "gui")]
+use crate::{clipboard, editor::Editor, modifier::traits::ModifierIndex, preview::Target};
 use crate::{
-    editor::Editor,
     modifier::{
         cation::{Cation, DynMod, Output},
-        traits::{Modifier, ModifierIndex},
+        metadata::{Category, Metadata},
+        traits::Modifier,
     },
     slot::ModifierSlot,
 };
@@ -25,16 +30,6 @@ impl List {
         }
     }
 
-    pub fn add_mod_from_index(&mut self, index: &ModifierIndex, editor: &mut Editor) {
-        let new = Cation::new(DynMod::from_index(index.clone()));
-        editor.select_cation(&new);
-        self.contents.push(ModifierSlot::from_cacher(new));
-    }
-
-    pub fn selected_mod_mut(&mut self, editor: &Editor) -> Option<&mut Cation<DynMod>> {
-        editor.selected_id().and_then(|id| self.mod_mut(id))
-    }
-
     pub fn mod_mut(&mut self, id: Uuid) -> Option<&mut Cation<DynMod>> {
         self.iter_mods_mut().find(|item| item.id == id)
     }
@@ -54,17 +49,170 @@ impl List {
             .collect()
     }
 
+    fn is_selected(slot: &ModifierSlot, selected: &HashSet<Uuid>) -> bool {
+        slot.mod_ref()
+            .is_some_and(|cation| selected.contains(&cation.id))
+    }
+
+    /// Moves the selected contents into a nested list, in the place of the first of them.
+    /// Returns the id of the new list.
+    pub fn group(&mut self, selected: &HashSet<Uuid>) -> Option<Uuid> {
+        let first = self
+            .contents
+            .iter()
+            .position(|slot| Self::is_selected(slot, selected))?;
+
+        let (grouped, rest) = std::mem::take(&mut self.contents)
+            .into_iter()
+            .partition::<Vec<_>, _>(|slot| Self::is_selected(slot, selected));
+
+        let group = Cation::new(DynMod::new(List { contents: grouped }));
+        let id = group.id;
+
+        self.contents = rest;
+        self.contents
+            .insert(first, ModifierSlot::from_cacher(group));
+        Some(id)
+    }
+
+    /// Whether a nested list can be replaced by its contents without losing the bypass, label
+    /// or keyframes set on it.
+    pub fn can_ungroup(cation: &Cation<DynMod>) -> bool {
+        !cation.bypass && cation.label.is_empty() && cation.keyframes.is_empty()
+    }
+
+    /// Replaces the selected nested lists by their contents, except those that
+    /// [can't be ungrouped](Self::can_ungroup). Returns the ids of those contents.
+    pub fn ungroup(&mut self, selected: &HashSet<Uuid>) -> Vec<Uuid> {
+        let mut ungrouped = Vec::new();
+
+        self.contents = std::mem::take(&mut self.contents)
+            .into_iter()
+            .flat_map(|slot| {
+                let contents = slot
+                    .mod_ref()
+                    .filter(|cation| selected.contains(&cation.id) && Self::can_ungroup(cation))
+                    .and_then(|cation| cation.modifier.modifier::<List>())
+                    .map(|list| list.contents.clone());
+
+                match contents {
+                    Some(contents) => {
+                        ungrouped.extend(
+                            contents
+                                .iter()
+                                .flat_map(|slot| slot.mod_ref())
+                                .map(|cation| cation.id),
+                        );
+                        contents
+                    }
+                    None => vec![slot],
+                }
+            })
+            .collect();
+
+        ungrouped
+    }
+
+    /// Moves every selected item one place, towards the end if `later`, keeping their order.
+    pub fn move_selected(&mut self, selected: &HashSet<Uuid>, later: bool) {
+        let len = self.contents.len();
+        let indices = (0..len).filter(|i| Self::is_selected(&self.contents[*i], selected));
+
+        let indices: Vec<usize> = if later {
+            indices.rev().collect()
+        } else {
+            indices.collect()
+        };
+
+        let mut blocked = if later { len } else { usize::MAX };
+        for i in indices {
+            let target = if later { i + 1 } else { i.wrapping_sub(1) };
+            if target >= len || target == blocked {
+                blocked = i;
+                continue;
+            }
+
+            self.contents.swap(i, target);
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl List {
+    pub fn add_mod_from_index(&mut self, index: &ModifierIndex, editor: &mut Editor) {
+        let new = Cation::new(DynMod::from_index(index.clone()));
+        editor.select_cation(&new);
+        self.contents.push(ModifierSlot::from_cacher(new));
+    }
+
+    pub fn selected_mod_mut(&mut self, editor: &Editor) -> Option<&mut Cation<DynMod>> {
+        editor.selected_id().and_then(|id| self.mod_mut(id))
+    }
+
     fn add_mod_button(&mut self, ui: &mut Ui, editor: &mut Editor) {
-        let mut slot = ModifierSlot::Empty;
-        slot.add_mod_widget(ui, editor);
-        if let ModifierSlot::Modifier(_) = slot {
-            editor.try_select_slot(&slot).ok();
-            self.contents.push(slot);
+        let target = editor.preview.parent.map(Target::End);
+        ui.horizontal(|ui| {
+            let mut slot = ModifierSlot::Empty;
+            slot.add_mod_widget(ui, editor, target);
+            if let ModifierSlot::Modifier(_) = slot {
+                editor.try_select_slot(&slot).ok();
+                self.contents.push(slot);
+            }
+
+            self.contents.extend(clipboard::paste_button(ui, editor));
+        });
+    }
+
+    /// Selects the range requested in the editor, if it starts and ends in the contents.
+    fn select_range_requested(&mut self, editor: &mut Editor) {
+        let position = |id: Uuid| {
+            self.contents
+                .iter()
+                .position(|slot| slot.mod_ref().is_some_and(|cation| cation.id == id))
+        };
+        let (Some(from), Some(to)) = (
+            editor.selected_id().and_then(position),
+            editor.select_range.and_then(position),
+        ) else {
+            return;
+        };
+
+        editor.select_range = None;
+        for cation in self.contents[from.min(to)..=from.max(to)]
+            .iter()
+            .flat_map(|slot| slot.mod_ref())
+        {
+            editor.selection.insert(cation.id);
         }
     }
+
+    /// Inserts the copy requested in the editor, if it goes after one of the contents.
+    fn insert_requested(&mut self, editor: &mut Editor) {
+        let Some(index) = editor.insert.as_ref().and_then(|(after, _)| {
+            self.contents
+                .iter()
+                .position(|slot| slot.mod_ref().is_some_and(|cation| cation.id == *after))
+        }) else {
+            return;
+        };
+
+        let (_, cation) = editor.insert.take().unwrap();
+        editor.select_cation(&cation);
+        self.contents
+            .insert(index + 1, ModifierSlot::from_cacher(cation));
+    }
 }
 
 impl Modifier for List {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Composite,
+            "📑",
+            "Applies modifiers one after another.",
+            &["group", "stack", "folder", "layers"],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
         {
             let mut borrow = &*input;
@@ -75,6 +223,7 @@ impl Modifier for List {
         }
     }
 
+    #[cfg(feature = "gui")]
     fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {
         self.add_mod_button(ui, editor);
 
@@ -119,6 +268,58 @@ impl Modifier for List {
                 new.reverse();
                 self.contents = new;
             });
+
+            self.insert_requested(editor);
+            self.select_range_requested(editor);
         }
     }
+
+    fn slots(&self) -> Vec<&ModifierSlot> {
+        self.contents.iter().collect()
+    }
+
+    fn slots_mut(&mut self) -> Vec<&mut ModifierSlot> {
+        self.contents.iter_mut().collect()
+    }
+
+    fn set_slot_count(&mut self, count: usize) {
+        self.contents.resize_with(count, ModifierSlot::default);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modifier::collection::invert::Invert;
+
+    fn group(bypass: bool) -> (Uuid, List) {
+        let mut group = Cation::new(DynMod::new(List::from_vec_mods(vec![Invert])));
+        group.bypass = bypass;
+        let id = group.id;
+        let list = List {
+            contents: vec![ModifierSlot::from_cacher(group)],
+        };
+        (id, list)
+    }
+
+    #[test]
+    fn ungroup_replaces_group_by_contents() {
+        let (id, mut list) = group(false);
+
+        let ungrouped = list.ungroup(&HashSet::from([id]));
+
+        assert_eq!(ungrouped.len(), 1);
+        assert_eq!(
+            list.iter_mods().map(|cation| cation.id).collect::<Vec<_>>(),
+            ungrouped
+        );
+    }
+
+    #[test]
+    fn ungroup_keeps_bypassed_group() {
+        let (id, mut list) = group(true);
+
+        assert!(list.ungroup(&HashSet::from([id])).is_empty());
+        assert!(list.mod_mut(id).is_some());
+    }
 }
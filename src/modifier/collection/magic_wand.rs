@@ -1,13 +1,19 @@
-use eframe::egui::{self, Color32, Context, Ui};
+#[cfg(feature = "gui")]
+use eframe::egui::{self, Context, Ui};
 
 use crate::{
     color::Color,
-    editor::Editor,
-    modifier::{cation::Output, traits::Modifier},
+    modifier::{
+        cation::Output,
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+        traits::Modifier,
+    },
     position::Position,
     slot::ModifierSlot,
-    view::View,
 };
+#[cfg(feature = "gui")]
+use crate::{editor::Editor, modifier::param, view::View};
 
 #[derive(Clone, PartialEq)]
 pub struct MagicWand {
@@ -26,6 +32,7 @@ impl Default for MagicWand {
     }
 }
 
+#[cfg(feature = "gui")]
 impl MagicWand {
     pub fn update(&mut self, ctx: &Context, view: &View) {
         if ctx.input(|input| input.pointer.primary_clicked()) && !ctx.wants_pointer_input() {
@@ -41,13 +48,22 @@ impl MagicWand {
             ui.add(
                 egui::DragValue::new(&mut self.threshold)
                     .speed(0.001)
-                    .clamp_range(0.0..=Color::from(Color32::WHITE).sum_rgb()),
+                    .clamp_range(0.0..=Color::WHITE.sum_rgb()),
             );
         });
     }
 }
 
 impl Modifier for MagicWand {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Draw,
+            "✨",
+            "Selects the area of similar colour around a clicked pixel.",
+            &["select", "selection", "mask"],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
         if let Some(target) = self.target {
             if let Some(child) = self.input.mod_mut() {
@@ -74,13 +90,45 @@ impl Modifier for MagicWand {
         }
     }
 
+    #[cfg(feature = "gui")]
     fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {
-        self.view_threshold(ui);
-        ui.label("input:");
-        self.input.view_with_frame(ui, editor, None);
+        param::view(self, ui, editor);
 
         if editor.is_modifier_selected::<Self>() {
             self.update(ui.ctx(), &editor.view);
         }
     }
+
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::float("threshold", 0.0..=Color::WHITE.sum_rgb(), 0.001),
+            Param::new("target", ParamKind::Position),
+            Param::new("input", ParamKind::Slot),
+        ]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "threshold" => Some(Value::Float(self.threshold)),
+            "target" => self.target.map(Value::Position),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "threshold" => self.threshold = value.as_f32().ok_or(WRONG_TYPE)?,
+            "target" => self.target = Some(value.as_position().ok_or(WRONG_TYPE)?),
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
+    }
+
+    fn slots(&self) -> Vec<&ModifierSlot> {
+        vec![&self.input]
+    }
+
+    fn slots_mut(&mut self) -> Vec<&mut ModifierSlot> {
+        vec![&mut self.input]
+    }
 }
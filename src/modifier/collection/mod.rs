@@ -3,7 +3,13 @@ use self::{
     brighten::Brighten,
     bucket::Bucket,
     contrast::Contrast,
+    expression::Expression,
     fill::Fill,
+    generator::{
+        canvas::Canvas, gradient::Gradient, noise::Noise, pattern::Pattern, voronoi::Voronoi,
+        GeneratorMod,
+    },
+    graph::Graph,
     grayscale::GrayScaleFilter,
     hue::Hue,
     invert::Invert,
@@ -11,17 +17,26 @@ use self::{
     magic_wand::MagicWand,
     overlay::Overlay,
     pencil::{rainbow::RainbowPencil, simple::SimplePencil, sort::PixelSorter, PencilMod},
+    reference::Reference,
     resize::Resize,
     source::Source,
 };
-use super::traits::Modifier;
+use super::{
+    param::Value,
+    traits::{Modifier, ModifierIndex},
+};
+#[cfg(feature = "gui")]
 use crate::editor::Editor;
+use crate::{plugin::plugins_collection, position::Position, presets::Preset};
 
 pub mod blur;
 pub mod brighten;
 pub mod bucket;
 pub mod contrast;
+pub mod expression;
 pub mod fill;
+pub mod generator;
+pub mod graph;
 pub mod grayscale;
 pub mod hue;
 pub mod invert;
@@ -29,24 +44,104 @@ pub mod list;
 pub mod magic_wand;
 pub mod overlay;
 pub mod pencil;
+pub mod reference;
 pub mod resize;
 pub mod source;
 
+/// Every built-in modifier.
+pub fn modifiers_collection() -> Vec<ModifierIndex> {
+    vec![
+        GrayScaleFilter::index(),
+        Source::index(),
+        Hue::index(),
+        Brighten::index(),
+        Contrast::index(),
+        Invert::index(),
+        Blur::index(),
+        Resize::index(),
+        Bucket::index(),
+        Fill::index(),
+        MagicWand::index(),
+        Overlay::index(),
+        List::index(),
+        Graph::index(),
+        Reference::index(),
+        PencilMod::<SimplePencil>::index(),
+        PencilMod::<RainbowPencil>::index(),
+        PencilMod::<PixelSorter>::index(),
+        Expression::index(),
+        GeneratorMod::<Canvas>::index(),
+        GeneratorMod::<Gradient>::index(),
+        GeneratorMod::<Pattern>::index(),
+        GeneratorMod::<Noise>::index(),
+        GeneratorMod::<Voronoi>::index(),
+    ]
+}
+
+/// Presets that ship with the built-in modifiers.
+pub fn builtin_presets() -> Vec<Preset> {
+    vec![
+        Preset::new::<Blur>("soft", [("sigma", Value::Float(1.0))]),
+        Preset::new::<Blur>("strong", [("sigma", Value::Float(8.0))]),
+        Preset::new::<Brighten>("lighter", [("amount", Value::Int(20))]),
+        Preset::new::<Brighten>("darker", [("amount", Value::Int(-20))]),
+        Preset::new::<Contrast>("punchy", [("amount", Value::Float(30.0))]),
+        Preset::new::<Contrast>("flat", [("amount", Value::Float(-30.0))]),
+        Preset::new::<Hue>("opposite", [("degrees", Value::Int(180))]),
+        Preset::new::<Resize>(
+            "half",
+            [
+                ("relative", Value::Bool(true)),
+                ("size", Value::Position(Position::new(50.0, 50.0))),
+            ],
+        ),
+        Preset::new::<Resize>(
+            "double",
+            [
+                ("relative", Value::Bool(true)),
+                ("size", Value::Position(Position::new(200.0, 200.0))),
+            ],
+        ),
+        Preset::new::<Resize>(
+            "pixel art x4",
+            [
+                ("relative", Value::Bool(true)),
+                ("size", Value::Position(Position::new(400.0, 400.0))),
+                ("filter", Value::Enum(0)),
+            ],
+        ),
+        Preset::new::<PencilMod<PixelSorter>>(
+            "vertical",
+            [("angle", Value::Float(90.0)), ("direction", Value::Enum(0))],
+        ),
+        Preset::new::<PencilMod<PixelSorter>>(
+            "horizontal",
+            [("angle", Value::Float(0.0)), ("direction", Value::Enum(0))],
+        ),
+        Preset::new::<PencilMod<PixelSorter>>(
+            "gentle",
+            [
+                ("threshold", Value::Float(0.5)),
+                ("direction", Value::Enum(2)),
+            ],
+        ),
+    ]
+}
+
+/// The built-in modifiers and the loaded plugins.
+pub fn available_modifiers() -> Vec<ModifierIndex> {
+    let mut index = modifiers_collection();
+    index.extend(plugins_collection());
+    index
+}
+
+#[cfg(feature = "gui")]
 pub fn init_modifiers_collection(editor: &mut Editor) {
-    editor.add_index(GrayScaleFilter::index());
-    editor.add_index(Source::index());
-    editor.add_index(Hue::index());
-    editor.add_index(Brighten::index());
-    editor.add_index(Contrast::index());
-    editor.add_index(Invert::index());
-    editor.add_index(Blur::index());
-    editor.add_index(Resize::index());
-    editor.add_index(Bucket::index());
-    editor.add_index(Fill::index());
-    editor.add_index(MagicWand::index());
-    editor.add_index(Overlay::index());
-    editor.add_index(List::index());
-    editor.add_index(PencilMod::<SimplePencil>::index());
-    editor.add_index(PencilMod::<RainbowPencil>::index());
-    editor.add_index(PencilMod::<PixelSorter>::index());
+    for index in available_modifiers() {
+        editor.add_index(index);
+    }
+
+    for preset in builtin_presets() {
+        editor.presets.add_builtin(preset);
+    }
 }
@@ -1,8 +1,15 @@
+#[cfg(feature = "gui")]
 use eframe::egui::Ui;
 
+#[cfg(feature = "gui")]
+use crate::{editor::Editor, modifier::param};
 use crate::{
-    editor::Editor,
-    modifier::{cation::Output, traits::Modifier},
+    modifier::{
+        cation::Output,
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+        traits::Modifier,
+    },
     position::Position,
     slot::ModifierSlot,
 };
@@ -11,7 +18,10 @@ use crate::{
 pub struct Overlay {
     pub target: Position,
     pub input: ModifierSlot,
+    #[cfg(feature = "gui")]
     dragging: bool,
+    #[cfg(feature = "gui")]
+    drag_target: Position,
 }
 
 impl Default for Overlay {
@@ -19,7 +29,10 @@ impl Default for Overlay {
         Self {
             target: Position::ZERO,
             input: Default::default(),
+            #[cfg(feature = "gui")]
             dragging: false,
+            #[cfg(feature = "gui")]
+            drag_target: Position::ZERO,
         }
     }
 }
@@ -31,6 +44,15 @@ impl PartialEq for Overlay {
 }
 
 impl Modifier for Overlay {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Composite,
+            "🗐",
+            "Draws the output of another modifier on top of the image.",
+            &["layer", "paste", "combine", "blend"],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
         if let Some(wrapped) = self.input.mod_mut() {
             if let Some(wrapped_output) = wrapped.output(&input).image.clone() {
@@ -41,29 +63,69 @@ impl Modifier for Overlay {
         }
     }
 
+    #[cfg(feature = "gui")]
     fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {
-        ui.label("input:");
-        self.input.view_with_frame(ui, editor, None);
+        param::view(self, ui, editor);
 
         if editor.is_modifier_selected::<Self>() {
-            if ui.rect_contains_pointer(editor.view.rect) {
+            if editor.view.hovered_pixel(ui.ctx()).is_some() {
                 if ui
                     .ctx()
                     .input(|input| input.pointer.any_pressed() && input.pointer.primary_down())
                 {
                     self.dragging = true;
+                    self.drag_target = self.target;
                 } else if ui.ctx().input(|input| input.pointer.any_released()) {
                     self.dragging = false;
                 }
             }
 
             if self.dragging && ui.ctx().input(|input| input.pointer.any_down()) {
-                self.target += Position::from(ui.ctx().input(|input| input.pointer.delta()))
+                self.drag_target += Position::from(ui.ctx().input(|input| input.pointer.delta()))
                     * ui.ctx().pixels_per_point()
                     / editor.view.scale;
+
+                let size = self
+                    .input
+                    .mod_ref()
+                    .and_then(|input| input.cached_output())
+                    .and_then(|output| output.image.as_ref())
+                    .map_or(Position::ZERO, |image| image.size());
+
+                self.target = editor.view.snap_rect(self.drag_target, size);
             } else {
                 self.dragging = false;
             }
         }
     }
+
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::new("target", ParamKind::Position),
+            Param::new("input", ParamKind::Slot),
+        ]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "target" => Some(Value::Position(self.target)),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "target" => self.target = value.as_position().ok_or(WRONG_TYPE)?,
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
+    }
+
+    fn slots(&self) -> Vec<&ModifierSlot> {
+        vec![&self.input]
+    }
+
+    fn slots_mut(&mut self) -> Vec<&mut ModifierSlot> {
+        vec![&mut self.input]
+    }
 }
@@ -1,27 +1,53 @@
+#[cfg(feature = "gui")]
 use eframe::egui::Ui;
 use uuid::Uuid;
 
 use crate::{
     color::Color,
-    editor::Editor,
     image::Image,
-    modifier::{cation::Output, traits::Modifier},
+    modifier::{
+        cation::Output,
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+        traits::Modifier,
+    },
     position::Position,
 };
+#[cfg(feature = "gui")]
+use crate::{editor::Editor, modifier::param};
 
 pub mod rainbow;
 pub mod simple;
 pub mod sort;
 
 pub trait Pencil {
+    fn metadata() -> Metadata
+    where
+        Self: Sized,
+    {
+        Metadata::new(Category::Draw, "✏", "", &[])
+    }
+
     fn pixel(&mut self, pixel: Position, image: &mut Image) -> Option<Color>;
-    fn view(&mut self, _ui: &mut Ui) {}
+
+    fn params(&self) -> Vec<Param> {
+        Vec::new()
+    }
+
+    fn get_param(&self, _name: &str) -> Option<Value> {
+        None
+    }
+
+    fn set_param(&mut self, _name: &str, _value: Value) -> Result<(), &'static str> {
+        Err(UNKNOWN)
+    }
 }
 
 #[derive(Clone, Default)]
 pub struct PencilMod<T> {
-    pixels: Vec<Position>,
-    pencil: T,
+    pub pixels: Vec<Position>,
+    pub pencil: T,
+    #[cfg(feature = "gui")]
     last_pixel: Option<Position>,
     cached: Option<Cached<T>>,
 }
@@ -41,11 +67,15 @@ impl<T: PartialEq> PartialEq for PencilMod<T> {
 }
 
 impl<T: Pencil + Default + PartialEq + Clone + 'static> Modifier for PencilMod<T> {
+    fn metadata() -> Metadata {
+        T::metadata()
+    }
+
     fn apply(&mut self, input: &mut Output) {
         let (mut prepared_pencil, mut prepared_image, prepared_pixels) =
             if self.cached.as_ref().is_some_and(|cache| {
                 cache.input_id == input.id
-                    && cache.pixels == self.pixels[0..cache.pixels.len()]
+                    && self.pixels.starts_with(&cache.pixels)
                     && cache.pencil == self.pencil
             }) {
                 (
@@ -79,8 +109,30 @@ impl<T: Pencil + Default + PartialEq + Clone + 'static> Modifier for PencilMod<T
         input.image = prepared_image;
     }
 
+    fn params(&self) -> Vec<Param> {
+        let mut params = self.pencil.params();
+        params.push(Param::new("pixels", ParamKind::Points));
+        params
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "pixels" => Some(Value::Points(self.pixels.clone())),
+            _ => self.pencil.get_param(name),
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "pixels" => self.pixels = value.into_points().ok_or(WRONG_TYPE)?,
+            _ => return self.pencil.set_param(name, value),
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "gui")]
     fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {
-        self.pencil.view(ui);
+        param::view(self, ui, editor);
 
         if editor.is_modifier_selected::<Self>() {
             if (ui.ctx().input(|input| input.pointer.primary_down()))
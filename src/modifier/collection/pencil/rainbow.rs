@@ -1,7 +1,13 @@
-use eframe::egui;
-
 use super::Pencil;
-use crate::{color::Color, image::Image, position::Position};
+use crate::{
+    color::Color,
+    image::Image,
+    modifier::{
+        metadata::{Category, Metadata},
+        param::{Param, Value, UNKNOWN, WRONG_TYPE},
+    },
+    position::Position,
+};
 
 #[derive(Clone, PartialEq)]
 pub struct RainbowPencil {
@@ -21,6 +27,15 @@ impl Default for RainbowPencil {
 }
 
 impl Pencil for RainbowPencil {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Draw,
+            "🎨",
+            "Draws lines that cycle through the colours.",
+            &["brush", "paint", "freehand", "colourful"],
+        )
+    }
+
     fn pixel(&mut self, pixel: Position, _: &mut Image) -> Option<Color> {
         if let Some(last_pixel) = self.last_pixel {
             if pixel != last_pixel {
@@ -37,40 +52,27 @@ impl Pencil for RainbowPencil {
         ))
     }
 
-    fn view(&mut self, ui: &mut egui::Ui) {
-        egui::Grid::new("")
-            .num_columns(2)
-            .spacing([40.0, 4.0])
-            .striped(true)
-            .show(ui, |ui| {
-                {
-                    let mut degrees = self.color_hsv.0 % 1.0 * 360.0;
-
-                    ui.label("current hue rotation:");
-                    ui.add(
-                        egui::DragValue::new(&mut degrees)
-                            .speed(1.0)
-                            .clamp_range(360.0..=0.0)
-                            .suffix("°"),
-                    );
-                    self.color_hsv.0 = degrees / 360.0;
-                }
-                ui.end_row();
-
-                {
-                    let mut rotation_degrees = self.rotation_per_pixel * 360.0;
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::float("hue", 0.0..=360.0, 1.0).suffix("°"),
+            Param::float("rotation", 0.0..=360.0, 0.01).suffix("°"),
+        ]
+    }
 
-                    ui.label("hue rotation per pixel:");
-                    ui.add(
-                        egui::DragValue::new(&mut rotation_degrees)
-                            .speed(0.01)
-                            .clamp_range(360.0..=0.0)
-                            .suffix("°"),
-                    );
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "hue" => Some(Value::Float(self.color_hsv.0 % 1.0 * 360.0)),
+            "rotation" => Some(Value::Float(self.rotation_per_pixel * 360.0)),
+            _ => None,
+        }
+    }
 
-                    self.rotation_per_pixel = rotation_degrees / 360.0;
-                }
-                ui.end_row();
-            });
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "hue" => self.color_hsv.0 = value.as_f32().ok_or(WRONG_TYPE)? / 360.0,
+            "rotation" => self.rotation_per_pixel = value.as_f32().ok_or(WRONG_TYPE)? / 360.0,
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
     }
 }
@@ -1,30 +1,57 @@
-use eframe::egui::{Color32, Ui};
-
 use super::Pencil;
-use crate::{color::Color, image::Image, position::Position};
+use crate::{
+    color::Color,
+    image::Image,
+    modifier::{
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+    },
+    position::Position,
+};
 
 #[derive(Clone, PartialEq)]
 pub struct SimplePencil {
-    color: Color32,
+    pub color: Color,
 }
 
 impl Default for SimplePencil {
     fn default() -> Self {
         Self {
-            color: Color32::BLACK,
+            color: Color::BLACK,
         }
     }
 }
 
 impl Pencil for SimplePencil {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Draw,
+            "✏",
+            "Draws lines in one colour.",
+            &["brush", "paint", "freehand", "pen"],
+        )
+    }
+
     fn pixel(&mut self, _: Position, _: &mut Image) -> Option<Color> {
-        Some(Color::from(self.color))
+        Some(self.color)
+    }
+
+    fn params(&self) -> Vec<Param> {
+        vec![Param::new("color", ParamKind::Color)]
     }
 
-    fn view(&mut self, ui: &mut Ui) {
-        ui.horizontal(|ui| {
-            ui.label("color:");
-            ui.color_edit_button_srgba(&mut self.color);
-        });
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "color" => Some(Value::Color(self.color)),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "color" => self.color = value.as_color().ok_or(WRONG_TYPE)?,
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
     }
 }
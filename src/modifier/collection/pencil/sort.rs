@@ -1,11 +1,17 @@
 use std::f32::consts::PI;
 
-use eframe::egui::{self, Color32, Ui};
-
 use super::Pencil;
-use crate::{applied::AppliedValue, color::Color, image::Image, position::Position};
-
-#[derive(Clone, PartialEq)]
+use crate::{
+    color::Color,
+    image::Image,
+    modifier::{
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+    },
+    position::Position,
+};
+
+#[derive(Clone, Copy, PartialEq)]
 enum SortDirection {
     Forward,
     Backward,
@@ -14,22 +20,31 @@ enum SortDirection {
 
 #[derive(Clone, PartialEq)]
 pub struct PixelSorter {
-    threshold: AppliedValue<f32>,
-    angle: AppliedValue<f32>,
+    threshold: f32,
+    angle: f32,
     direction: SortDirection,
 }
 
 impl Default for PixelSorter {
     fn default() -> Self {
         Self {
-            angle: AppliedValue::new(PI / 2.0),
-            threshold: AppliedValue::new(0.1),
+            angle: PI / 2.0,
+            threshold: 0.1,
             direction: SortDirection::Both,
         }
     }
 }
 
 impl Pencil for PixelSorter {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Filter,
+            "🔃",
+            "Sorts pixels by brightness along the drawn strokes.",
+            &["glitch", "pixel sort", "streaks"],
+        )
+    }
+
     fn pixel(&mut self, pixel: Position, image: &mut Image) -> Option<Color> {
         let mut positions = Vec::<Position>::new();
 
@@ -53,7 +68,7 @@ impl Pencil for PixelSorter {
                     break;
                 };
 
-                if (current_color.sum_rgb() - next_color.sum_rgb()).abs() < *self.threshold {
+                if (current_color.sum_rgb() - next_color.sum_rgb()).abs() < self.threshold {
                     positions.push(next_position);
                 } else {
                     break;
@@ -76,7 +91,7 @@ impl Pencil for PixelSorter {
                     break;
                 };
 
-                if (current_color.sum_rgb() - next_color.sum_rgb()).abs() < *self.threshold {
+                if (current_color.sum_rgb() - next_color.sum_rgb()).abs() < self.threshold {
                     positions.push(next_position);
                 } else {
                     break;
@@ -107,50 +122,37 @@ impl Pencil for PixelSorter {
         None
     }
 
-    fn view(&mut self, ui: &mut Ui) {
-        egui::Grid::new("")
-            .num_columns(2)
-            .spacing([40.0, 4.0])
-            .striped(true)
-            .show(ui, |ui| {
-                {
-                    ui.label("threshold:");
-                    self.threshold.view(|value| {
-                        ui.add(
-                            egui::DragValue::new(value)
-                                .speed(0.01)
-                                .clamp_range(0.0..=Color::from(Color32::WHITE).sum_rgb()),
-                        )
-                    });
-
-                    ui.end_row();
-                }
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::float("threshold", 0.0..=Color::WHITE.sum_rgb(), 0.01),
+            Param::float("angle", -360.0..=360.0, 1.0).suffix("°"),
+            Param::new("direction", ParamKind::Enum(&["Forward", "Back", "Both"])),
+        ]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "threshold" => Some(Value::Float(self.threshold)),
+            "angle" => Some(Value::Float(self.angle.to_degrees())),
+            "direction" => Some(Value::Enum(self.direction as usize)),
+            _ => None,
+        }
+    }
 
-                self.angle.view(|value| {
-                    let mut degrees = value.to_degrees();
-
-                    ui.label("angle:");
-                    let response = ui.add(
-                        egui::DragValue::new(&mut degrees)
-                            .speed(1)
-                            .clamp_range(-360.0..=360.0)
-                            .suffix("°"),
-                    );
-
-                    *value = degrees.to_radians();
-                    ui.end_row();
-                    response
-                });
-
-                {
-                    ui.label("direction:");
-                    ui.horizontal(|ui| {
-                        ui.selectable_value(&mut self.direction, SortDirection::Forward, "Forward");
-                        ui.selectable_value(&mut self.direction, SortDirection::Backward, "Back");
-                        ui.selectable_value(&mut self.direction, SortDirection::Both, "Both");
-                    });
-                    ui.end_row();
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "threshold" => self.threshold = value.as_f32().ok_or(WRONG_TYPE)?,
+            "angle" => self.angle = value.as_f32().ok_or(WRONG_TYPE)?.to_radians(),
+            "direction" => {
+                self.direction = match value.as_enum().ok_or(WRONG_TYPE)? {
+                    0 => SortDirection::Forward,
+                    1 => SortDirection::Backward,
+                    2 => SortDirection::Both,
+                    _ => return Err(WRONG_TYPE),
                 }
-            });
+            }
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,356 @@
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "gui")]
+use eframe::egui::{self, Ui};
+use uuid::Uuid;
+
+use super::{graph::Graph, list::List};
+#[cfg(feature = "gui")]
+use crate::editor::Editor;
+use crate::{
+    modifier::{
+        cation::{Cation, Output},
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+        traits::Modifier,
+    },
+    slot::ModifierSlot,
+};
+
+/// Outputs the cached result of another modifier in the project, so it can be used in several
+/// places without copying it. The project fills in the result before applying, see [`resolve`].
+#[derive(Clone, Default)]
+pub struct Reference {
+    pub target: Option<Uuid>,
+    output: Option<Output>,
+    pub error: Option<String>,
+}
+
+impl PartialEq for Reference {
+    fn eq(&self, other: &Self) -> bool {
+        self.target == other.target
+            && self.output.as_ref().map(|output| output.id)
+                == other.output.as_ref().map(|output| output.id)
+    }
+}
+
+impl Reference {
+    pub fn new(target: Uuid) -> Self {
+        Self {
+            target: Some(target),
+            ..Default::default()
+        }
+    }
+}
+
+impl Modifier for Reference {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Composite,
+            "🔗",
+            "Reuses the result of another modifier in the project.",
+            &["link", "instance", "clone", "reuse"],
+        )
+    }
+
+    fn apply(&mut self, input: &mut Output) {
+        if let Some(output) = &self.output {
+            input.image = output.image.clone();
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {
+        let label = |id: Uuid| {
+            editor
+                .modifiers
+                .iter()
+                .find(|(candidate, _)| *candidate == id)
+                .map_or("(deleted)", |(_, label)| label.as_str())
+                .to_string()
+        };
+        let selected = self.target.map_or("(none)".to_string(), label);
+
+        let mut hovered = None;
+        let response = egui::ComboBox::from_label("target")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for (id, label) in &editor.modifiers {
+                    let response = ui.selectable_value(&mut self.target, Some(*id), label);
+                    if response.hovered() {
+                        hovered = Some(*id);
+                    }
+                }
+            })
+            .response;
+
+        if response.hovered() {
+            hovered = self.target;
+        }
+        if hovered.is_some() {
+            editor.highlight = hovered;
+        }
+
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+
+    fn params(&self) -> Vec<Param> {
+        vec![Param::new("target", ParamKind::Text)]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "target" => Some(Value::Text(
+                self.target.map_or(String::new(), |id| id.to_string()),
+            )),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "target" => {
+                let text = value.as_text().ok_or(WRONG_TYPE)?;
+                self.target = if text.is_empty() {
+                    None
+                } else {
+                    Some(Uuid::parse_str(text).map_err(|_| "not a modifier id")?)
+                };
+            }
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
+    }
+}
+
+/// Gives every reference below `root` the current output of its target, or an error when it has
+/// none, the target was deleted or its output depends on the reference. Returns whether any
+/// output changed, in which case `root` has to be applied again.
+pub fn resolve<T: Modifier + Clone + PartialEq>(root: &mut Cation<T>) -> bool {
+    let mut updates = HashMap::new();
+    let flow = Flow::new(root);
+
+    for cation in root.descendants() {
+        let Some(reference) = cation.modifier.modifier::<Reference>() else {
+            continue;
+        };
+
+        let result = match reference.target.map(|id| root.find(id)) {
+            None => Err("no target selected"),
+            Some(None) => Err("the referenced modifier was deleted"),
+            Some(Some(target)) if flow.dependents(root, cation.id).contains(&target.id) => {
+                Err("reference cycle")
+            }
+            Some(Some(target)) => Ok(target.cached_output()),
+        };
+
+        let current = reference.output.as_ref().map(|output| output.id);
+        let update = match result {
+            Ok(output) if output.map(|output| output.id) == current => None,
+            Ok(output) => Some(output.cloned()),
+            Err(_) if current.is_none() => None,
+            Err(_) => Some(None),
+        };
+        let error = result.err().map(str::to_string);
+
+        updates.insert(cation.id, (update, error));
+    }
+
+    let mut changed = false;
+
+    root.for_each_descendant_mut(&mut |cation| {
+        let Some((update, error)) = updates.remove(&cation.id) else {
+            return;
+        };
+        let Some(reference) = cation.modifier.modifier_mut::<Reference>() else {
+            return;
+        };
+
+        if let Some(output) = update {
+            reference.output = output;
+            changed = true;
+        }
+        reference.error = error;
+    });
+
+    changed
+}
+
+/// How outputs flow through the project: the modifier holding each one, the modifiers taking
+/// its output as their input and the references to it.
+#[derive(Default)]
+struct Flow {
+    parent: HashMap<Uuid, Uuid>,
+    feeds: HashMap<Uuid, Vec<Uuid>>,
+    references: HashMap<Uuid, Vec<Uuid>>,
+}
+
+impl Flow {
+    fn new<T: Modifier + Clone + PartialEq>(root: &Cation<T>) -> Self {
+        let mut flow = Self::default();
+        flow.add(root.id, root.modifier.slots(), T::name() == List::name());
+        flow
+    }
+
+    fn add(&mut self, parent: Uuid, slots: Vec<&ModifierSlot>, chained: bool) {
+        let children = slots
+            .into_iter()
+            .flat_map(|slot| slot.mod_ref())
+            .collect::<Vec<_>>();
+
+        for (i, child) in children.iter().enumerate() {
+            self.parent.insert(child.id, parent);
+
+            // every modifier in a list gets the output of the one before it
+            if chained {
+                if let Some(next) = children.get(i + 1) {
+                    self.feeds.entry(child.id).or_default().push(next.id);
+                }
+            }
+
+            if let Some(target) = child
+                .modifier
+                .modifier::<Reference>()
+                .and_then(|reference| reference.target)
+            {
+                self.references.entry(target).or_default().push(child.id);
+            }
+
+            match child.modifier.modifier::<Graph>() {
+                Some(graph) => self.add_graph(child.id, graph),
+                None => self.add(
+                    child.id,
+                    child.modifier.slots(),
+                    child.modifier.modifier::<List>().is_some(),
+                ),
+            }
+        }
+    }
+
+    fn add_graph(&mut self, parent: Uuid, graph: &Graph) {
+        let cations = graph
+            .nodes
+            .iter()
+            .filter_map(|node| Some((node.id, node.slot.mod_ref()?.id)))
+            .collect::<HashMap<_, _>>();
+
+        for node in &graph.nodes {
+            let Some(id) = cations.get(&node.id) else {
+                continue;
+            };
+            for source in node
+                .inputs
+                .values()
+                .filter_map(|source| cations.get(source))
+            {
+                self.feeds.entry(*source).or_default().push(*id);
+            }
+        }
+
+        for node in &graph.nodes {
+            self.add(parent, vec![&node.slot], false);
+        }
+    }
+
+    /// Every modifier whose output changes with the output of `start`, including itself.
+    fn dependents<T: Modifier + Clone + PartialEq>(
+        &self,
+        root: &Cation<T>,
+        start: Uuid,
+    ) -> HashSet<Uuid> {
+        let mut found = HashSet::new();
+        let mut stack = vec![start];
+
+        while let Some(id) = stack.pop() {
+            if !found.insert(id) {
+                continue;
+            }
+
+            stack.extend(self.parent.get(&id));
+            stack.extend(self.references.get(&id).into_iter().flatten());
+
+            // what is nested in a modifier is applied to its input as well
+            for fed in self.feeds.get(&id).into_iter().flatten() {
+                stack.push(*fed);
+                if let Some(cation) = root.find(*fed) {
+                    stack.extend(cation.descendants().into_iter().map(|cation| cation.id));
+                }
+            }
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modifier::{cation::DynMod, collection::invert::Invert};
+
+    fn list(contents: Vec<Cation<DynMod>>) -> Cation<List> {
+        Cation::new(List {
+            contents: contents
+                .into_iter()
+                .map(ModifierSlot::from_cacher)
+                .collect(),
+        })
+    }
+
+    fn error(root: &Cation<List>, id: Uuid) -> Option<String> {
+        root.find(id)?
+            .modifier
+            .modifier::<Reference>()?
+            .error
+            .clone()
+    }
+
+    #[test]
+    fn target_after_reference_is_a_cycle() {
+        let target = Cation::new(DynMod::new(Invert));
+        let reference = Cation::new(DynMod::new(Reference::new(target.id)));
+        let id = reference.id;
+        let mut root = list(vec![reference, target]);
+
+        resolve(&mut root);
+        root.output(&Output::new_empty());
+
+        assert!(!resolve(&mut root));
+        assert_eq!(error(&root, id).as_deref(), Some("reference cycle"));
+    }
+
+    #[test]
+    fn target_containing_reference_is_a_cycle() {
+        let reference = Cation::new(DynMod::new(Reference::default()));
+        let id = reference.id;
+        let group = Cation::new(DynMod::new(List {
+            contents: vec![ModifierSlot::from_cacher(reference)],
+        }));
+        let group_id = group.id;
+        let mut root = list(vec![group]);
+        root.for_each_descendant_mut(&mut |cation| {
+            if let Some(reference) = cation.modifier.modifier_mut::<Reference>() {
+                reference.target = Some(group_id);
+            }
+        });
+
+        resolve(&mut root);
+        assert_eq!(error(&root, id).as_deref(), Some("reference cycle"));
+    }
+
+    #[test]
+    fn target_before_reference_resolves() {
+        let target = Cation::new(DynMod::new(Invert));
+        let reference = Cation::new(DynMod::new(Reference::new(target.id)));
+        let id = reference.id;
+        let mut root = list(vec![target, reference]);
+
+        root.output(&Output::new_empty());
+        assert!(resolve(&mut root));
+        root.output(&Output::new_empty());
+
+        assert!(!resolve(&mut root));
+        assert_eq!(error(&root, id), None);
+    }
+}
@@ -1,20 +1,33 @@
-use eframe::egui::{self, Ui};
 use image::imageops::FilterType;
 
 use crate::{
-    editor::Editor,
-    modifier::{cation::Output, traits::Modifier},
+    modifier::{
+        cation::Output,
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+        traits::Modifier,
+    },
     position::Position,
 };
 
+const FILTERS: [FilterType; 5] = [
+    FilterType::Nearest,
+    FilterType::Triangle,
+    FilterType::CatmullRom,
+    FilterType::Gaussian,
+    FilterType::Lanczos3,
+];
+
+const FILTER_NAMES: [&str; 5] = ["Nearest Neighbor", "Linear", "Cubic", "Gaussian", "Lanczos"];
+
 #[derive(Clone, PartialEq)]
 pub struct Resize {
-    size: Size,
-    filter: FilterType,
+    pub size: Size,
+    pub filter: FilterType,
 }
 
 #[derive(Clone, PartialEq)]
-enum Size {
+pub enum Size {
     Absolute(Position),
     Relative(Position),
 }
@@ -29,6 +42,15 @@ impl Default for Resize {
 }
 
 impl Modifier for Resize {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Transform,
+            "📐",
+            "Scales the image to a size or percentage.",
+            &["scale", "size", "upscale", "downscale", "shrink"],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
         if let Some(image) = &mut input.image {
             match self.size {
@@ -43,102 +65,55 @@ impl Modifier for Resize {
         }
     }
 
-    fn view(&mut self, ui: &mut Ui, _: &mut Editor) {
-        match self.size {
-            Size::Absolute(_) => {
-                if ui.checkbox(&mut false, "relative").changed() {
-                    self.size = Size::Relative(Position::new(100.0, 100.0))
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::new("relative", ParamKind::Bool),
+            Param::new("size", ParamKind::Position),
+            Param::new("filter", ParamKind::Enum(&FILTER_NAMES)),
+        ]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "relative" => Some(Value::Bool(matches!(self.size, Size::Relative(_)))),
+            "size" => match self.size {
+                Size::Absolute(size) | Size::Relative(size) => Some(Value::Position(size)),
+            },
+            "filter" => FILTERS
+                .iter()
+                .position(|filter| *filter == self.filter)
+                .map(Value::Enum),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "relative" => {
+                let relative = value.as_bool().ok_or(WRONG_TYPE)?;
+                match self.size {
+                    Size::Absolute(_) if relative => {
+                        self.size = Size::Relative(Position::new(100.0, 100.0))
+                    }
+                    Size::Relative(_) if !relative => {
+                        self.size = Size::Absolute(Position::new(100.0, 100.0))
+                    }
+                    _ => (),
                 }
             }
-            Size::Relative(_) => {
-                if ui.checkbox(&mut true, "relative").changed() {
-                    self.size = Size::Absolute(Position::new(100.0, 100.0))
+            "size" => {
+                let new = value.as_position().ok_or(WRONG_TYPE)?;
+                match &mut self.size {
+                    Size::Absolute(size) => *size = new.max(Position::ONE),
+                    Size::Relative(size) => *size = new.max(Position::ZERO),
                 }
             }
-        }
-
-        match &mut self.size {
-            Size::Absolute(size) => {
-                ui.horizontal(|ui| {
-                    ui.label("new height:");
-                    ui.add(
-                        egui::DragValue::new(&mut size.x)
-                            .clamp_range(1..=u32::MAX)
-                            .suffix("px"),
-                    );
-                });
-
-                ui.horizontal(|ui| {
-                    ui.label("new width:");
-                    ui.add(
-                        egui::DragValue::new(&mut size.y)
-                            .clamp_range(1..=u32::MAX)
-                            .suffix("px"),
-                    );
-                });
-            }
-            Size::Relative(size) => {
-                ui.horizontal(|ui| {
-                    ui.label("new height:");
-                    ui.add(
-                        egui::DragValue::new(&mut size.x)
-                            .clamp_range(f32::MIN_POSITIVE..=f32::MAX)
-                            .suffix("%"),
-                    );
-                });
-
-                ui.horizontal(|ui| {
-                    ui.label("new width:");
-                    ui.add(
-                        egui::DragValue::new(&mut size.y)
-                            .clamp_range(f32::MIN_POSITIVE..=f32::MAX)
-                            .suffix("%"),
-                    );
-                });
+            "filter" => {
+                let index = value.as_enum().ok_or(WRONG_TYPE)?;
+                self.filter = *FILTERS.get(index).ok_or(WRONG_TYPE)?;
             }
+            _ => return Err(UNKNOWN),
         }
-
-        ui.horizontal(|ui| {
-            ui.label("filter:");
-            egui::ComboBox::from_id_source("filter")
-                .selected_text(display_filter(self.filter))
-                .show_ui(ui, |ui| {
-                    ui.selectable_value(
-                        &mut self.filter,
-                        FilterType::Nearest,
-                        display_filter(FilterType::Nearest),
-                    );
-                    ui.selectable_value(
-                        &mut self.filter,
-                        FilterType::Triangle,
-                        display_filter(FilterType::Triangle),
-                    );
-                    ui.selectable_value(
-                        &mut self.filter,
-                        FilterType::CatmullRom,
-                        display_filter(FilterType::CatmullRom),
-                    );
-                    ui.selectable_value(
-                        &mut self.filter,
-                        FilterType::Gaussian,
-                        display_filter(FilterType::Gaussian),
-                    );
-                    ui.selectable_value(
-                        &mut self.filter,
-                        FilterType::Lanczos3,
-                        display_filter(FilterType::Lanczos3),
-                    );
-                });
-        });
-    }
-}
-
-fn display_filter(filter: FilterType) -> &'static str {
-    match filter {
-        FilterType::Nearest => "Nearest Neighbor",
-        FilterType::Triangle => "Linear",
-        FilterType::CatmullRom => "Cubic",
-        FilterType::Gaussian => "Gaussian",
-        FilterType::Lanczos3 => "Lanczos",
+        Ok(())
     }
 }
@@ -1,37 +1,76 @@
+#[cfg(feature = "gui")]
+use std::sync::mpsc::Receiver;
 use std::{
     path::{Path, PathBuf},
-    str::FromStr,
-    sync::mpsc::Receiver,
+    sync::Arc,
 };
 
-use eframe::egui::{TextEdit, Ui};
+#[cfg(feature = "gui")]
+use eframe::egui::Ui;
 
+#[cfg(feature = "gui")]
 use crate::{
     editor::Editor,
     file_picker::{FilePicker, PickerResult},
+};
+use crate::{
     image::Image,
-    modifier::{cation::Output, traits::Modifier},
+    modifier::{
+        cation::Output,
+        metadata::{Category, Metadata},
+        param::{Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+        traits::Modifier,
+    },
 };
 
 #[derive(Default)]
 pub struct Source {
     pub path: PathBuf,
+    /// An image kept in the project instead of read from `path`, like one pasted from the
+    /// clipboard.
+    embedded: Option<Embedded>,
+    #[cfg(feature = "gui")]
     receiver: Option<Receiver<PickerResult>>,
 }
 
+/// The image together with its PNG encoding, encoded once as the project is stored after every
+/// change.
+#[derive(Clone)]
+struct Embedded {
+    image: Arc<Image>,
+    png: Arc<[u8]>,
+}
+
 impl Source {
     pub fn new(path: impl AsRef<Path>) -> Self {
         Self {
             path: path.as_ref().to_path_buf(),
-            ..Default::default()
+            embedded: None,
+            #[cfg(feature = "gui")]
+            receiver: None,
         }
     }
+
+    /// A source of an image that is not in a file, saved as part of the project.
+    pub fn embedded(image: Image) -> Result<Self, String> {
+        let png = image.to_png().map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            embedded: Some(Embedded {
+                image: Arc::new(image),
+                png: png.into(),
+            }),
+            ..Default::default()
+        })
+    }
 }
 
 impl Clone for Source {
     fn clone(&self) -> Self {
         Self {
             path: self.path.clone(),
+            embedded: self.embedded.clone(),
+            #[cfg(feature = "gui")]
             receiver: None,
         }
     }
@@ -40,27 +79,64 @@ impl Clone for Source {
 impl PartialEq for Source {
     fn eq(&self, other: &Self) -> bool {
         self.path == other.path
+            && match (&self.embedded, &other.embedded) {
+                (Some(a), Some(b)) => a.png == b.png,
+                (None, None) => true,
+                _ => false,
+            }
     }
 }
 
 impl Modifier for Source {
+    fn metadata() -> Metadata {
+        Metadata::new(
+            Category::Source,
+            "🗁",
+            "Loads an image file, or holds an image pasted or dropped into the editor.",
+            &[
+                "input",
+                "file",
+                "open",
+                "load",
+                "image",
+                "paste",
+                "clipboard",
+            ],
+        )
+    }
+
     fn apply(&mut self, input: &mut Output) {
-        *input = Output::new(Image::open(&self.path).ok())
+        let image = match &self.embedded {
+            Some(embedded) => Some(Image::clone(&embedded.image)),
+            None => Image::open(&self.path).ok(),
+        };
+
+        *input = Output::new(image)
     }
 
+    #[cfg(feature = "gui")]
     fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {
         if let Some(receiver) = &self.receiver {
             if let Ok(PickerResult::PickedLoad(result)) = receiver.try_recv() {
                 self.path = result;
+                self.embedded = None;
             }
         }
 
         ui.horizontal(|ui| {
-            ui.label("path:");
-            let mut string = self.path.to_string_lossy().to_string();
-            if ui.add(TextEdit::singleline(&mut string)).changed() {
-                self.path = PathBuf::from_str(&string).unwrap();
-            }
+            match &self.embedded {
+                Some(embedded) => {
+                    let (width, height) = embedded.image.as_rgba8().dimensions();
+                    ui.label(format!("embedded image, {width}×{height}"));
+                }
+                None => {
+                    ui.label("path:");
+                    let mut path = self.path.to_string_lossy().to_string();
+                    if ui.text_edit_singleline(&mut path).changed() {
+                        self.path = PathBuf::from(path);
+                    }
+                }
+            };
         });
 
         ui.add_enabled_ui(!editor.picker.is_open(), |ui| {
@@ -69,4 +145,79 @@ impl Modifier for Source {
             }
         });
     }
+
+    fn params(&self) -> Vec<Param> {
+        vec![Param::new("path", ParamKind::Text)]
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        match name {
+            "path" => Some(Value::Text(self.path.to_string_lossy().to_string())),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        match name {
+            "path" => self.path = PathBuf::from(value.as_text().ok_or(WRONG_TYPE)?),
+            _ => return Err(UNKNOWN),
+        }
+        Ok(())
+    }
+
+    /// The embedded image as PNG.
+    fn data(&self) -> Option<Arc<[u8]>> {
+        self.embedded.as_ref().map(|embedded| embedded.png.clone())
+    }
+
+    fn set_data(&mut self, data: Arc<[u8]>) -> Result<(), &'static str> {
+        let image = Image::from_memory(&data).map_err(|_| "embedded image could not be decoded")?;
+        self.embedded = Some(Embedded {
+            image: Arc::new(image),
+            png: data,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    use super::*;
+    use crate::modifier::{
+        cation::{Cation, DynMod},
+        save::Node,
+    };
+
+    #[test]
+    fn embedded_image_round_trip() {
+        // sizes whose encodings end in every kind of base64 padding
+        for width in 1..=6 {
+            let image = Image::from_dyn(DynamicImage::ImageRgba8(RgbaImage::from_fn(
+                width,
+                3,
+                |x, y| Rgba([x as u8 * 40, y as u8 * 80, 7, 255 - x as u8]),
+            )));
+            let cation = Cation::new(DynMod::new(Source::embedded(image.clone()).unwrap()));
+
+            let json = serde_json::to_string(&Node::from_cation(&cation)).unwrap();
+            let node: Node = serde_json::from_str(&json).unwrap();
+            let mut loaded = node.into_cation().unwrap();
+
+            let output = loaded.output(&Output::new_empty());
+            assert_eq!(output.image.as_ref().unwrap().as_rgba8(), image.as_rgba8());
+            assert!(loaded.modifier == cation.modifier);
+        }
+    }
+
+    #[test]
+    fn invalid_data_is_an_error() {
+        assert!(serde_json::from_str::<Node>(r#"{ "modifier": "Source", "data": "a!" }"#).is_err());
+
+        // "bm90IGEgcG5n" is "not a png"
+        let node: Node =
+            serde_json::from_str(r#"{ "modifier": "Source", "data": "bm90IGEgcG5n" }"#).unwrap();
+        assert!(node.into_cation().is_err());
+    }
 }
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "gui")]
+use eframe::egui::Ui;
+use serde::{Deserialize, Serialize};
+
+use super::param::Value;
+#[cfg(feature = "gui")]
+use super::{
+    cation::{Cation, DynMod},
+    param::ParamKind,
+    traits::Modifier,
+};
+
+/// Parameter values at points in time, per parameter name.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Keyframes {
+    tracks: BTreeMap<String, Vec<Keyframe>>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    pub time: f32,
+    pub value: Value,
+}
+
+impl Keyframes {
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    pub fn tracks(&self) -> impl Iterator<Item = (&str, &[Keyframe])> {
+        self.tracks
+            .iter()
+            .map(|(name, track)| (name.as_str(), track.as_slice()))
+    }
+
+    pub fn track(&self, param: &str) -> &[Keyframe] {
+        self.tracks.get(param).map_or(&[], |track| track.as_slice())
+    }
+
+    /// Adds a keyframe, replacing the one at the same time.
+    pub fn insert(&mut self, param: &str, time: f32, value: Value) {
+        let track = self.tracks.entry(param.to_string()).or_default();
+        track.retain(|keyframe| keyframe.time != time);
+
+        let index = track.partition_point(|keyframe| keyframe.time < time);
+        track.insert(index, Keyframe { time, value });
+    }
+
+    pub fn remove(&mut self, param: &str, time: f32) {
+        if let Some(track) = self.tracks.get_mut(param) {
+            track.retain(|keyframe| keyframe.time != time);
+            if track.is_empty() {
+                self.tracks.remove(param);
+            }
+        }
+    }
+
+    /// Time of the last keyframe of any parameter.
+    pub fn end(&self) -> f32 {
+        self.tracks
+            .values()
+            .filter_map(|track| track.last())
+            .map(|keyframe| keyframe.time)
+            .fold(0.0, f32::max)
+    }
+
+    /// Value of the parameter at the given time, interpolated between the surrounding keyframes
+    /// and held before the first and after the last one.
+    pub fn sample(&self, param: &str, time: f32) -> Option<Value> {
+        let track = self.tracks.get(param)?;
+        let next = track.partition_point(|keyframe| keyframe.time <= time);
+
+        match (
+            next.checked_sub(1).map(|index| &track[index]),
+            track.get(next),
+        ) {
+            (Some(previous), Some(next)) => {
+                let t = (time - previous.time) / (next.time - previous.time);
+                Some(previous.value.lerp(&next.value, t))
+            }
+            (Some(keyframe), None) | (None, Some(keyframe)) => Some(keyframe.value.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Lists the keyframes of every parameter, with a button to key the current value at `time`.
+#[cfg(feature = "gui")]
+pub fn view(cation: &mut Cation<DynMod>, ui: &mut Ui, time: f32) {
+    let params = cation
+        .modifier
+        .params()
+        .into_iter()
+        .filter(|param| {
+            !matches!(
+                param.kind,
+                ParamKind::Slot | ParamKind::Points | ParamKind::List
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if params.is_empty() {
+        return;
+    }
+
+    ui.collapsing("keyframes", |ui| {
+        for param in params {
+            ui.horizontal(|ui| {
+                ui.label(param.name);
+
+                if ui
+                    .small_button("◆")
+                    .on_hover_text(format!("key the current value at {time:.2}s"))
+                    .clicked()
+                {
+                    if let Some(value) = cation.modifier.get_param(param.name) {
+                        cation.keyframes.insert(param.name, time, value);
+                    }
+                }
+
+                let mut removed = None;
+                for keyframe in cation.keyframes.track(param.name) {
+                    if ui
+                        .selectable_label(keyframe.time == time, format!("{:.2}s", keyframe.time))
+                        .on_hover_text("remove")
+                        .clicked()
+                    {
+                        removed = Some(keyframe.time);
+                    }
+                }
+
+                if let Some(time) = removed {
+                    cation.keyframes.remove(param.name, time);
+                }
+            });
+        }
+    });
+}
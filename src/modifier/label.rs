@@ -0,0 +1,83 @@
+#[cfg(feature = "gui")]
+use eframe::egui::{Color32, TextEdit, Ui};
+use serde::{Deserialize, Serialize};
+
+use super::cation::{Cation, DynMod};
+use crate::color::Color;
+
+/// What the user calls a modifier, to tell it apart from others of the same type.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Label {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<Color>,
+}
+
+impl Label {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The custom name of the modifier, or the name of its type.
+pub fn title(cation: &Cation<DynMod>) -> &str {
+    if cation.label.name.is_empty() {
+        &cation.modifier.index.name
+    } else {
+        &cation.label.name
+    }
+}
+
+/// Whether the modifier's type, name or note contains the query, ignoring case.
+pub fn matches(cation: &Cation<DynMod>, query: &str) -> bool {
+    let query = query.to_lowercase();
+    [
+        cation.modifier.index.name.as_str(),
+        &cation.label.name,
+        &cation.label.note,
+    ]
+    .iter()
+    .any(|text| text.to_lowercase().contains(&query))
+}
+
+/// Whether the modifier or anything nested in it matches the query.
+pub fn matches_nested(cation: &Cation<DynMod>, query: &str) -> bool {
+    matches(cation, query)
+        || cation
+            .descendants()
+            .into_iter()
+            .any(|cation| matches(cation, query))
+}
+
+/// Button to rename a modifier, write a note and pick a colour tag.
+#[cfg(feature = "gui")]
+pub fn menu(label: &mut Label, ui: &mut Ui) {
+    ui.menu_button("🏷", |ui| {
+        ui.horizontal(|ui| {
+            ui.label("name:");
+            ui.text_edit_singleline(&mut label.name);
+        });
+
+        ui.label("note:");
+        ui.add(TextEdit::multiline(&mut label.note).desired_rows(3));
+
+        ui.horizontal(|ui| {
+            ui.label("tag:");
+
+            let mut tagged = label.tag.is_some();
+            if ui.checkbox(&mut tagged, "").changed() {
+                label.tag = tagged.then_some(Color::from_rgba(0.9, 0.3, 0.3, 1.0));
+            }
+
+            if let Some(tag) = &mut label.tag {
+                let mut color = Color32::from(*tag);
+                if ui.color_edit_button_srgba(&mut color).changed() {
+                    *tag = Color::from(color);
+                }
+            }
+        });
+    });
+}
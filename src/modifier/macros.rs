@@ -0,0 +1,568 @@
+use std::{any::TypeId, rc::Rc};
+
+#[cfg(feature = "gui")]
+use eframe::egui::{Button, Id, TextEdit, Ui};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "gui")]
+use super::param::ParamKind;
+use super::{
+    cation::{Cation, DynMod, Output},
+    collection::{available_modifiers, list::List},
+    metadata::{Category, Metadata},
+    param::{self, Param, Value, UNKNOWN},
+    save::Node,
+    traits::{Modifier, ModifierIndex},
+};
+use crate::slot::ModifierSlot;
+#[cfg(feature = "gui")]
+use crate::{editor::Editor, project::Project};
+
+/// A parameter of a modifier inside a macro, made available as a parameter of the macro itself.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exposed {
+    pub name: String,
+    /// Slot indices leading from the macro's list to the modifier.
+    pub path: Vec<usize>,
+    pub param: String,
+}
+
+/// A saved list of modifiers that can be added like a built-in modifier.
+pub struct MacroDef {
+    pub name: String,
+    pub list: List,
+    pub exposed: Vec<Exposed>,
+}
+
+/// A definition as stored in a project file.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacroFile {
+    name: String,
+    contents: Vec<Option<Node>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    exposed: Vec<Exposed>,
+}
+
+/// An instance of a macro. Its modifiers are a copy of the definition's that can only be changed
+/// through the exposed parameters, so redefining the macro can replace them.
+#[derive(Clone, Default, PartialEq)]
+pub struct Macro {
+    pub name: String,
+    list: List,
+    exposed: Vec<Exposed>,
+}
+
+/// The macros defined in a project.
+#[derive(Default)]
+pub struct Macros {
+    definitions: Vec<Rc<MacroDef>>,
+}
+
+impl MacroDef {
+    /// Checks that the macro does not shadow a built-in modifier or contain itself.
+    pub fn new(name: String, list: List, exposed: Vec<Exposed>) -> Result<Self, String> {
+        if name.trim().is_empty() {
+            return Err("a macro needs a name".to_string());
+        }
+
+        if available_modifiers().iter().any(|index| index.name == name) {
+            return Err(format!("`{name}` is already a modifier"));
+        }
+
+        if contains(list.slots(), &name) {
+            return Err(format!("`{name}` can not contain itself"));
+        }
+
+        Ok(Self {
+            name,
+            list,
+            exposed,
+        })
+    }
+}
+
+/// Index entry that instances the macro.
+pub fn index(def: &Rc<MacroDef>) -> ModifierIndex {
+    let captured = def.clone();
+    ModifierIndex {
+        name: def.name.clone(),
+        id: TypeId::of::<Macro>(),
+        instancer: Box::new(move || Box::new(Macro::from_def(&captured)) as Box<dyn Modifier>),
+        metadata: Metadata::new(
+            Category::Composite,
+            "📎",
+            "A macro saved in this project.",
+            &["macro"],
+        ),
+    }
+}
+
+impl MacroFile {
+    fn from_def(def: &MacroDef) -> Self {
+        Self {
+            name: def.name.clone(),
+            contents: def.list.contents.iter().map(Node::from_slot).collect(),
+            exposed: def.exposed.clone(),
+        }
+    }
+
+    fn to_def(&self, index: &[ModifierIndex]) -> Result<MacroDef, String> {
+        let contents = self
+            .contents
+            .iter()
+            .map(|node| {
+                node.clone().map_or(Ok(ModifierSlot::Empty), |node| {
+                    Ok(ModifierSlot::from_cacher(node.into_cation_with(index)?))
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        MacroDef::new(self.name.clone(), List { contents }, self.exposed.clone())
+    }
+}
+
+impl Macro {
+    pub fn from_def(def: &MacroDef) -> Self {
+        Self {
+            name: def.name.clone(),
+            list: renewed(&def.list),
+            exposed: def.exposed.clone(),
+        }
+    }
+
+    /// Follows a new definition, keeping the values of exposed parameters that still exist.
+    fn redefine(&mut self, def: &MacroDef) {
+        let values = self
+            .exposed
+            .iter()
+            .filter_map(|exposed| Some((exposed.name.clone(), self.get_param(&exposed.name)?)))
+            .collect::<Vec<_>>();
+
+        *self = Self::from_def(def);
+
+        for (name, value) in values {
+            self.set_param(&name, value).ok();
+        }
+    }
+
+    /// A copy of the modifiers as a list that can be edited and saved as the macro again.
+    pub fn unpack(&self) -> List {
+        renewed(&self.list)
+    }
+}
+
+impl Modifier for Macro {
+    fn apply(&mut self, input: &mut Output) {
+        self.list.apply(input);
+    }
+
+    fn params(&self) -> Vec<Param> {
+        self.exposed
+            .iter()
+            .filter_map(|exposed| {
+                let inner = resolve(self.list.slots(), &exposed.path)?;
+                let param = inner
+                    .modifier
+                    .params()
+                    .into_iter()
+                    .find(|param| param.name == exposed.param)?;
+                Some(Param::new(param::intern(&exposed.name), param.kind))
+            })
+            .collect()
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        let exposed = self.exposed.iter().find(|exposed| exposed.name == name)?;
+        resolve(self.list.slots(), &exposed.path)?
+            .modifier
+            .get_param(&exposed.param)
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        let exposed = self
+            .exposed
+            .iter()
+            .find(|exposed| exposed.name == name)
+            .ok_or(UNKNOWN)?;
+        resolve_mut(self.list.slots_mut(), &exposed.path)
+            .ok_or(UNKNOWN)?
+            .modifier
+            .set_param(&exposed.param, value)
+    }
+
+    fn slots(&self) -> Vec<&ModifierSlot> {
+        self.list.slots()
+    }
+
+    fn slots_mut(&mut self) -> Vec<&mut ModifierSlot> {
+        self.list.slots_mut()
+    }
+}
+
+impl Macros {
+    pub fn indices(&self) -> Vec<ModifierIndex> {
+        self.definitions.iter().map(index).collect()
+    }
+
+    /// Adds or replaces a definition and updates every instance of it, including those inside
+    /// other macros.
+    pub fn define(&mut self, def: Rc<MacroDef>, mut root: Vec<&mut ModifierSlot>) {
+        let mut pending = vec![def];
+
+        while let Some(def) = pending.pop() {
+            match self
+                .definitions
+                .iter()
+                .position(|other| other.name == def.name)
+            {
+                Some(position) => self.definitions[position] = def.clone(),
+                None => self.definitions.push(def.clone()),
+            }
+
+            update(root.iter_mut().map(|slot| &mut **slot).collect(), &def);
+
+            for other in &self.definitions {
+                if other.name != def.name && contains(other.list.slots(), &def.name) {
+                    let mut list = other.list.clone();
+                    update(list.slots_mut(), &def);
+                    pending.push(Rc::new(MacroDef {
+                        name: other.name.clone(),
+                        list,
+                        exposed: other.exposed.clone(),
+                    }));
+                }
+            }
+        }
+    }
+
+    pub fn to_files(&self) -> Vec<MacroFile> {
+        self.definitions
+            .iter()
+            .map(|def| MacroFile::from_def(def))
+            .collect()
+    }
+
+    /// Recreates the definitions, whatever order they depend on each other in.
+    pub fn from_files(files: Vec<MacroFile>) -> Result<Self, String> {
+        let mut macros = Self::default();
+        let mut pending = files;
+
+        while !pending.is_empty() {
+            let mut index = available_modifiers();
+            index.extend(macros.indices());
+
+            let before = pending.len();
+            let mut error = None;
+
+            pending.retain(|file| match file.to_def(&index) {
+                Ok(def) => {
+                    macros.definitions.push(Rc::new(def));
+                    false
+                }
+                Err(err) => {
+                    error.get_or_insert(format!("macro {}: {err}", file.name));
+                    true
+                }
+            });
+
+            if let Some(err) = error.filter(|_| pending.len() == before) {
+                return Err(err);
+            }
+        }
+
+        Ok(macros)
+    }
+}
+
+fn renewed(list: &List) -> List {
+    let mut list = list.clone();
+    for cation in list.iter_mods_mut() {
+        cation.renew_ids();
+    }
+    list
+}
+
+fn update(slots: Vec<&mut ModifierSlot>, def: &MacroDef) {
+    for cation in slots.into_iter().flat_map(|slot| slot.mod_mut()) {
+        if let Some(instance) = cation.modifier.modifier_mut::<Macro>() {
+            if instance.name == def.name {
+                instance.redefine(def);
+            }
+        }
+
+        update(cation.modifier.slots_mut(), def);
+    }
+}
+
+/// Whether any of the slots holds an instance of the macro, also through other macros.
+fn contains(slots: Vec<&ModifierSlot>, name: &str) -> bool {
+    slots
+        .into_iter()
+        .flat_map(|slot| slot.mod_ref())
+        .any(|cation| {
+            cation.modifier.modifier::<Macro>().is_some_and(|instance| {
+                instance.name == name || contains(instance.list.slots(), name)
+            }) || contains(cation.modifier.slots(), name)
+        })
+}
+
+fn resolve<'a>(slots: Vec<&'a ModifierSlot>, path: &[usize]) -> Option<&'a Cation<DynMod>> {
+    let (first, rest) = path.split_first()?;
+    let cation = slots.into_iter().nth(*first)?.mod_ref()?;
+
+    if rest.is_empty() {
+        Some(cation)
+    } else {
+        resolve(cation.modifier.slots(), rest)
+    }
+}
+
+fn resolve_mut<'a>(
+    slots: Vec<&'a mut ModifierSlot>,
+    path: &[usize],
+) -> Option<&'a mut Cation<DynMod>> {
+    let (first, rest) = path.split_first()?;
+    let cation = slots.into_iter().nth(*first)?.mod_mut()?;
+
+    if rest.is_empty() {
+        Some(cation)
+    } else {
+        resolve_mut(cation.modifier.slots_mut(), rest)
+    }
+}
+
+/// The modifier saved as a macro and its parameters chosen so far, kept between frames.
+#[cfg(feature = "gui")]
+#[derive(Clone, Default)]
+struct Draft {
+    name: String,
+    exposed: Vec<Exposed>,
+    error: Option<String>,
+}
+
+/// Slot header button to save a list as a macro, or to unpack a macro for editing. A selection
+/// of modifiers is saved with [`selection_menu`].
+#[cfg(feature = "gui")]
+pub fn header(slot: &mut ModifierSlot, ui: &mut Ui, editor: &mut Editor) {
+    let Some(cation) = slot.mod_ref() else {
+        return;
+    };
+
+    if let Some(instance) = cation.modifier.modifier::<Macro>() {
+        if ui
+            .button("✏")
+            .on_hover_text("edit the macro, save it again to update every instance")
+            .clicked()
+        {
+            let unpacked = Cation::new(DynMod::new(instance.unpack()));
+            let draft = Draft {
+                name: instance.name.clone(),
+                exposed: instance.exposed.clone(),
+                error: None,
+            };
+            ui.data_mut(|data| data.insert_temp(draft_id(&unpacked), draft));
+            *slot = ModifierSlot::from_cacher(unpacked);
+        }
+    } else if let Some(list) = cation.modifier.modifier::<List>() {
+        let id = draft_id(cation);
+        let mut draft: Draft = ui.data_mut(|data| data.get_temp(id)).unwrap_or_default();
+        let mut saved = None;
+        ui.menu_button("📦", |ui| saved = save_menu(ui, list, &mut draft))
+            .response
+            .on_hover_text("save as macro");
+
+        if let Some(def) = saved {
+            ui.data_mut(|data| data.remove::<Draft>(id));
+            *slot = ModifierSlot::from_cacher(Cation::new(DynMod::from_index(index(&def))));
+            editor.new_macro = Some(def);
+        } else {
+            ui.data_mut(|data| data.insert_temp(id, draft));
+        }
+    }
+}
+
+/// Selection bar menu to save the selected modifiers of a list as a macro, replacing them by an
+/// instance of it.
+#[cfg(feature = "gui")]
+pub fn selection_menu(ui: &mut Ui, editor: &mut Editor, project: &mut Project) {
+    let selected = |list: &List| {
+        list.contents
+            .iter()
+            .filter(|slot| {
+                slot.mod_ref()
+                    .is_some_and(|cation| editor.selection.contains(&cation.id))
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    let mut lists = std::iter::once(&project.root.modifier)
+        .chain(
+            project
+                .root
+                .descendants()
+                .into_iter()
+                .filter_map(|cation| cation.modifier.modifier::<List>()),
+        )
+        .map(selected)
+        .filter(|contents| !contents.is_empty());
+    let (Some(contents), None) = (lists.next(), lists.next()) else {
+        ui.add_enabled(false, Button::new("📦"))
+            .on_disabled_hover_text("select modifiers of one list to save them as a macro");
+        return;
+    };
+    let list = List { contents };
+
+    let id = Id::new("selection macro");
+    let mut draft: Draft = ui.data_mut(|data| data.get_temp(id)).unwrap_or_default();
+    let mut saved = None;
+    ui.menu_button("📦", |ui| saved = save_menu(ui, &list, &mut draft))
+        .response
+        .on_hover_text("save as macro");
+
+    let Some(def) = saved else {
+        ui.data_mut(|data| data.insert_temp(id, draft));
+        return;
+    };
+
+    ui.data_mut(|data| data.remove::<Draft>(id));
+    let instance = Cation::new(DynMod::from_index(index(&def)));
+    for group in project.group(&editor.selection) {
+        if let Some(cation) = project.root.find_mut(group) {
+            *cation = instance.clone();
+        }
+    }
+    editor.select_cation(&instance);
+    editor.new_macro = Some(def);
+}
+
+/// The name and parameters to expose of a macro, returning it once it is saved.
+#[cfg(feature = "gui")]
+fn save_menu(ui: &mut Ui, list: &List, draft: &mut Draft) -> Option<Rc<MacroDef>> {
+    ui.add(TextEdit::singleline(&mut draft.name).hint_text("name"));
+
+    ui.label("parameters:");
+    for (path, inner) in with_paths(list.slots()) {
+        for param in inner.modifier.params() {
+            if param.kind == ParamKind::Slot {
+                continue;
+            }
+
+            let position = draft
+                .exposed
+                .iter()
+                .position(|exposed| exposed.path == path && exposed.param == param.name);
+            let text = format!(
+                "{} {}.{}",
+                path.iter()
+                    .map(|index| format!("#{index}"))
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                inner.modifier.index.name,
+                param.name
+            );
+
+            if ui.selectable_label(position.is_some(), text).clicked() {
+                match position {
+                    Some(position) => {
+                        draft.exposed.remove(position);
+                    }
+                    None => draft.exposed.push(Exposed {
+                        name: unique_name(&draft.exposed, param.name),
+                        path: path.clone(),
+                        param: param.name.to_string(),
+                    }),
+                }
+            }
+        }
+    }
+
+    if let Some(error) = &draft.error {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    }
+
+    if ui
+        .add_enabled(!draft.name.is_empty(), Button::new("save as macro"))
+        .clicked()
+    {
+        match MacroDef::new(draft.name.clone(), list.clone(), draft.exposed.clone()) {
+            Ok(def) => {
+                ui.close_menu();
+                return Some(Rc::new(def));
+            }
+            Err(err) => draft.error = Some(err),
+        }
+    }
+
+    None
+}
+
+#[cfg(feature = "gui")]
+fn draft_id(cation: &Cation<DynMod>) -> Id {
+    Id::new(cation.id).with("macro")
+}
+
+/// Every modifier in the slots with the slot indices leading to it, depth first.
+#[cfg(feature = "gui")]
+fn with_paths(slots: Vec<&ModifierSlot>) -> Vec<(Vec<usize>, &Cation<DynMod>)> {
+    slots
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, slot)| Some((index, slot.mod_ref()?)))
+        .flat_map(|(index, cation)| {
+            std::iter::once((vec![index], cation)).chain(
+                with_paths(cation.modifier.slots())
+                    .into_iter()
+                    .map(move |(mut path, inner)| {
+                        path.insert(0, index);
+                        (path, inner)
+                    }),
+            )
+        })
+        .collect()
+}
+
+#[cfg(feature = "gui")]
+fn unique_name(exposed: &[Exposed], name: &str) -> String {
+    let taken = |candidate: &str| exposed.iter().any(|exposed| exposed.name == candidate);
+
+    if !taken(name) {
+        return name.to_string();
+    }
+
+    (2..)
+        .map(|number| format!("{name} {number}"))
+        .find(|candidate| !taken(candidate))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modifier::collection::brighten::Brighten;
+
+    #[test]
+    fn inner_modifiers_are_found_and_animated() {
+        let def = MacroDef::new(
+            "lighter".to_string(),
+            List::from_vec_mods(vec![Brighten { value: 0 }]),
+            Vec::new(),
+        )
+        .unwrap();
+        let mut root = Cation::new(List {
+            contents: vec![ModifierSlot::from_mod(Macro::from_def(&def))],
+        });
+
+        let inner = root.descendants()[1].id;
+        root.find_mut(inner)
+            .unwrap()
+            .keyframes
+            .insert("amount", 0.0, Value::Int(30));
+        root.animate(0.0);
+
+        assert_eq!(
+            root.find(inner).unwrap().modifier.get_param("amount"),
+            Some(Value::Int(30))
+        );
+    }
+}
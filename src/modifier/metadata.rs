@@ -0,0 +1,56 @@
+use serde::Deserialize;
+
+/// The group a modifier is listed under when adding one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Adjust,
+    #[default]
+    Filter,
+    Transform,
+    Draw,
+    Composite,
+    Source,
+}
+
+impl Category {
+    pub const ALL: [Self; 6] = [
+        Self::Adjust,
+        Self::Filter,
+        Self::Transform,
+        Self::Draw,
+        Self::Composite,
+        Self::Source,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Adjust => "Adjust",
+            Self::Filter => "Filter",
+            Self::Transform => "Transform",
+            Self::Draw => "Draw",
+            Self::Composite => "Composite",
+            Self::Source => "Source",
+        }
+    }
+}
+
+/// What is shown about a modifier when adding one, and searched besides its name.
+#[derive(Clone, Default)]
+pub struct Metadata {
+    pub category: Category,
+    pub icon: String,
+    pub description: String,
+    pub keywords: Vec<String>,
+}
+
+impl Metadata {
+    pub fn new(category: Category, icon: &str, description: &str, keywords: &[&str]) -> Self {
+        Self {
+            category,
+            icon: icon.to_string(),
+            description: description.to_string(),
+            keywords: keywords.iter().map(|keyword| keyword.to_string()).collect(),
+        }
+    }
+}
@@ -1,3 +1,9 @@
 pub mod cation;
 pub mod collection;
+pub mod keyframes;
+pub mod label;
+pub mod macros;
+pub mod metadata;
+pub mod param;
+pub mod save;
 pub mod traits;
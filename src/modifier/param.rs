@@ -0,0 +1,450 @@
+use std::{collections::BTreeSet, ops::RangeInclusive, sync::Mutex};
+
+#[cfg(feature = "gui")]
+use eframe::egui::{self, Color32, DragValue, Id, Response, TextEdit, Ui};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "gui")]
+use crate::editor::Editor;
+use crate::{color::Color, position::Position};
+
+/// Description of a named parameter of a modifier.
+#[derive(Clone, PartialEq)]
+pub struct Param {
+    pub name: &'static str,
+    pub kind: ParamKind,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum ParamKind {
+    Float {
+        range: RangeInclusive<f32>,
+        speed: f32,
+        suffix: &'static str,
+    },
+    Int {
+        range: RangeInclusive<i32>,
+        suffix: &'static str,
+    },
+    Color,
+    Enum(&'static [&'static str]),
+    Bool,
+    Position,
+    Text,
+    Points,
+    /// Structured values only edited by the modifier's own view, like the wiring of a graph.
+    List,
+    /// A nested modifier, accessed through [`Modifier::slot`](super::traits::Modifier::slot)
+    /// instead of a [`Value`].
+    Slot,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Value {
+    Float(f32),
+    Int(i32),
+    Color(Color),
+    Enum(usize),
+    Bool(bool),
+    Position(Position),
+    Text(String),
+    Points(Vec<Position>),
+    List(Vec<Value>),
+}
+
+impl Param {
+    pub fn float(name: &'static str, range: RangeInclusive<f32>, speed: f32) -> Self {
+        Self {
+            name,
+            kind: ParamKind::Float {
+                range,
+                speed,
+                suffix: "",
+            },
+        }
+    }
+
+    pub fn int(name: &'static str, range: RangeInclusive<i32>) -> Self {
+        Self {
+            name,
+            kind: ParamKind::Int { range, suffix: "" },
+        }
+    }
+
+    pub fn new(name: &'static str, kind: ParamKind) -> Self {
+        Self { name, kind }
+    }
+
+    pub fn suffix(mut self, text: &'static str) -> Self {
+        match &mut self.kind {
+            ParamKind::Float { suffix, .. } | ParamKind::Int { suffix, .. } => *suffix = text,
+            _ => (),
+        }
+        self
+    }
+}
+
+impl ParamKind {
+    /// Parses a value as written on the command line, e.g. `3`, `true`, `#FF0000FF`, `10,20`
+    /// or the name of an enum variant.
+    pub fn parse(&self, text: &str) -> Result<Value, String> {
+        let text = text.trim();
+        let invalid = || format!("invalid value `{text}`");
+
+        match self {
+            Self::Float { range, .. } => text
+                .parse::<f32>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .map(|value| Value::Float(value.clamp(*range.start(), *range.end())))
+                .ok_or_else(invalid),
+            Self::Int { range, .. } => text
+                .parse::<i32>()
+                .map(|value| Value::Int(value.clamp(*range.start(), *range.end())))
+                .map_err(|_| invalid()),
+            Self::Color => Color::from_hex(text).map(Value::Color).ok_or_else(invalid),
+            Self::Enum(variants) => variants
+                .iter()
+                .position(|variant| variant.eq_ignore_ascii_case(text))
+                .or_else(|| text.parse().ok().filter(|index| *index < variants.len()))
+                .map(Value::Enum)
+                .ok_or_else(|| format!("expected one of {}", variants.join(", "))),
+            Self::Bool => text.parse().map(Value::Bool).map_err(|_| invalid()),
+            Self::Position => {
+                let (x, y) = text.split_once(',').ok_or_else(invalid)?;
+                match (x.trim().parse::<f32>(), y.trim().parse::<f32>()) {
+                    (Ok(x), Ok(y)) if x.is_finite() && y.is_finite() => {
+                        Ok(Value::Position(Position::new(x, y)))
+                    }
+                    _ => Err(invalid()),
+                }
+            }
+            Self::Text => Ok(Value::Text(text.to_string())),
+            Self::Points | Self::List | Self::Slot => {
+                Err("parameter can not be set from text".to_string())
+            }
+        }
+    }
+}
+
+impl Value {
+    /// Interpolates towards `other`, for values that can be blended, otherwise holds `self`
+    /// until the end.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        match (self, other) {
+            (Self::Float(a), Self::Float(b)) => Self::Float(a + (b - a) * t),
+            (Self::Int(a), Self::Int(b)) => {
+                Self::Int((*a as f32 + (*b - *a) as f32 * t).round() as i32)
+            }
+            (Self::Color(a), Self::Color(b)) => Self::Color(Color::from_rgba(
+                a.r + (b.r - a.r) * t,
+                a.g + (b.g - a.g) * t,
+                a.b + (b.b - a.b) * t,
+                a.a + (b.a - a.a) * t,
+            )),
+            (Self::Position(a), Self::Position(b)) => Self::Position(a.lerp(*b, t)),
+            _ if t >= 1.0 => other.clone(),
+            _ => self.clone(),
+        }
+    }
+
+    /// Keeps numbers within the range of the parameter, as its widgets do. NaN becomes the start
+    /// of the range.
+    pub fn clamped(self, kind: &ParamKind) -> Self {
+        match (self, kind) {
+            (Self::Float(value), ParamKind::Float { range, .. }) if value.is_nan() => {
+                Self::Float(*range.start())
+            }
+            (Self::Float(value), ParamKind::Float { range, .. }) => {
+                Self::Float(value.clamp(*range.start(), *range.end()))
+            }
+            (Self::Int(value), ParamKind::Int { range, .. }) => {
+                Self::Int(value.clamp(*range.start(), *range.end()))
+            }
+            (value, _) => value,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Self::Float(value) => Some(*value),
+            Self::Int(value) => Some(*value as f32),
+            _ => None,
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            Self::Int(value) => Some(*value),
+            Self::Float(value) => Some(value.round() as i32),
+            _ => None,
+        }
+    }
+
+    pub fn as_color(&self) -> Option<Color> {
+        match self {
+            Self::Color(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_enum(&self) -> Option<usize> {
+        match self {
+            Self::Enum(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_position(&self) -> Option<Position> {
+        match self {
+            Self::Position(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn into_points(self) -> Option<Vec<Position>> {
+        match self {
+            Self::Points(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn into_list(self) -> Option<Vec<Value>> {
+        match self {
+            Self::List(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Gives a parameter name that is only known at runtime the `'static` lifetime of [`Param::name`].
+/// Every distinct name is leaked once.
+pub fn intern(name: &str) -> &'static str {
+    static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+    let mut names = NAMES.lock().unwrap();
+    if let Some(interned) = names.get(name) {
+        return interned;
+    }
+
+    let interned: &'static str = Box::leak(name.to_string().into_boxed_str());
+    names.insert(interned);
+    interned
+}
+
+/// Like [`intern`], for the variants of an enum parameter.
+pub fn intern_variants(variants: &[String]) -> &'static [&'static str] {
+    static LISTS: Mutex<BTreeSet<&'static [&'static str]>> = Mutex::new(BTreeSet::new());
+
+    let variants = variants
+        .iter()
+        .map(|variant| intern(variant))
+        .collect::<Vec<_>>();
+
+    let mut lists = LISTS.lock().unwrap();
+    if let Some(interned) = lists.get(&variants[..]) {
+        return interned;
+    }
+
+    let interned: &'static [&'static str] = variants.leak();
+    lists.insert(interned);
+    interned
+}
+
+/// Errors for [`Modifier::set_param`](super::traits::Modifier::set_param).
+pub const UNKNOWN: &str = "unknown parameter";
+pub const WRONG_TYPE: &str = "wrong value type";
+
+/// Shows a widget for every parameter of the modifier. Values that are dragged or typed are only
+/// set once the widget is released, so expensive modifiers do not recompute on every frame.
+#[cfg(feature = "gui")]
+pub fn view<M: super::traits::Modifier + ?Sized>(
+    modifier: &mut M,
+    ui: &mut Ui,
+    editor: &mut Editor,
+) {
+    let id = ui.id();
+
+    egui::Grid::new(id.with("params"))
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for param in modifier.params() {
+                if param.kind == ParamKind::Slot {
+                    continue;
+                }
+
+                ui.label(format!("{}:", param.name));
+
+                let value = modifier.get_param(param.name);
+                if let Some(value) = view_value(ui, id.with(param.name), &param.kind, value) {
+                    modifier.set_param(param.name, value).ok();
+                }
+
+                ui.end_row();
+            }
+        });
+
+    let slots = modifier
+        .params()
+        .into_iter()
+        .filter(|param| param.kind == ParamKind::Slot);
+    for (index, param) in slots.enumerate() {
+        if let Some(slot) = modifier.slot_mut(param.name) {
+            ui.label(format!("{}:", param.name));
+            slot.view_with_frame(ui, editor, index, None);
+        }
+    }
+}
+
+/// Returns the new value when it should be applied.
+#[cfg(feature = "gui")]
+fn view_value(ui: &mut Ui, id: Id, kind: &ParamKind, value: Option<Value>) -> Option<Value> {
+    match (kind, value) {
+        (
+            ParamKind::Float {
+                range,
+                speed,
+                suffix,
+            },
+            Some(Value::Float(value)),
+        ) => deferred(ui, id, value, |ui, value| {
+            ui.add(
+                DragValue::new(value)
+                    .clamp_range(range.clone())
+                    .speed(*speed)
+                    .suffix(*suffix),
+            )
+        })
+        .map(Value::Float),
+        (ParamKind::Int { range, suffix }, Some(Value::Int(value))) => {
+            deferred(ui, id, value, |ui, value| {
+                ui.add(
+                    DragValue::new(value)
+                        .clamp_range(range.clone())
+                        .suffix(*suffix),
+                )
+            })
+            .map(Value::Int)
+        }
+        (ParamKind::Color, Some(Value::Color(value))) => {
+            let mut color = Color32::from(value);
+            ui.color_edit_button_srgba(&mut color)
+                .changed()
+                .then(|| Value::Color(Color::from(color)))
+        }
+        (ParamKind::Enum(variants), Some(Value::Enum(mut value))) => {
+            let changed = egui::ComboBox::from_id_source(id)
+                .selected_text(variants.get(value).copied().unwrap_or_default())
+                .show_ui(ui, |ui| {
+                    variants.iter().enumerate().any(|(index, variant)| {
+                        ui.selectable_value(&mut value, index, *variant).changed()
+                    })
+                })
+                .inner
+                .unwrap_or(false);
+            changed.then_some(Value::Enum(value))
+        }
+        (ParamKind::Bool, Some(Value::Bool(mut value))) => ui
+            .checkbox(&mut value, "")
+            .changed()
+            .then_some(Value::Bool(value)),
+        (ParamKind::Position, Some(Value::Position(value))) => {
+            ui.horizontal(|ui| {
+                let x = deferred(ui, id.with("x"), value.x, |ui, x| {
+                    ui.add(DragValue::new(x).prefix("x: "))
+                });
+                let y = deferred(ui, id.with("y"), value.y, |ui, y| {
+                    ui.add(DragValue::new(y).prefix("y: "))
+                });
+                (x.is_some() || y.is_some()).then(|| {
+                    Value::Position(Position::new(x.unwrap_or(value.x), y.unwrap_or(value.y)))
+                })
+            })
+            .inner
+        }
+        (ParamKind::Text, Some(Value::Text(mut value))) => ui
+            .add(TextEdit::singleline(&mut value))
+            .changed()
+            .then_some(Value::Text(value)),
+        (ParamKind::Points, Some(Value::Points(points))) => {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} points", points.len()));
+                ui.add_enabled(!points.is_empty(), egui::Button::new("clear"))
+                    .clicked()
+                    .then(|| Value::Points(Vec::new()))
+            })
+            .inner
+        }
+        (ParamKind::List, Some(Value::List(values))) => {
+            ui.weak(format!("{} items", values.len()));
+            None
+        }
+        _ => {
+            ui.weak("(none)");
+            None
+        }
+    }
+}
+
+/// Edits a copy kept in egui's memory while the widget is in use and hands it back once it is
+/// released.
+#[cfg(feature = "gui")]
+fn deferred<T: Copy + Send + Sync + 'static>(
+    ui: &mut Ui,
+    id: Id,
+    value: T,
+    widget: impl FnOnce(&mut Ui, &mut T) -> Response,
+) -> Option<T> {
+    let mut temp = ui.data(|data| data.get_temp(id)).unwrap_or(value);
+    let response = widget(ui, &mut temp);
+
+    if response.dragged() || response.has_focus() {
+        ui.data_mut(|data| data.insert_temp(id, temp));
+        None
+    } else {
+        ui.data_mut(|data| data.remove::<T>(id));
+        (response.drag_released() || response.lost_focus() || response.changed()).then_some(temp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rejects_non_finite_floats() {
+        let kind = Param::float("sigma", 0.0..=100.0, 0.1).kind;
+
+        assert_eq!(kind.parse("250"), Ok(Value::Float(100.0)));
+        for text in ["NaN", "inf", "-infinity"] {
+            assert!(kind.parse(text).is_err(), "{text}");
+        }
+        assert!(ParamKind::Position.parse("1,NaN").is_err());
+    }
+
+    #[test]
+    fn values_are_clamped_to_the_range() {
+        let kind = Param::int("degrees", 0..=360).kind;
+        assert_eq!(Value::Int(720).clamped(&kind), Value::Int(360));
+        assert_eq!(Value::Bool(true).clamped(&kind), Value::Bool(true));
+
+        let kind = Param::float("sigma", 0.01..=10.0, 0.01).kind;
+        assert_eq!(Value::Float(f32::NAN).clamped(&kind), Value::Float(0.01));
+        assert_eq!(Value::Float(-1.0).clamped(&kind), Value::Float(0.01));
+    }
+}
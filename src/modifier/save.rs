@@ -0,0 +1,170 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use uuid::Uuid;
+
+use super::{
+    cation::{Cation, DynMod},
+    collection::available_modifiers,
+    keyframes::Keyframes,
+    label::Label,
+    param::{ParamKind, Value},
+    traits::{Modifier, ModifierIndex},
+};
+use crate::slot::ModifierSlot;
+
+/// A modifier with its parameters, keyframes and nested slots, as stored in a file.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Node {
+    pub modifier: String,
+    /// Kept so references to the modifier still find it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, Value>,
+    /// See [`Modifier::data`], stored as base64.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_data",
+        deserialize_with = "deserialize_data"
+    )]
+    pub data: Option<Arc<[u8]>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slots: Vec<Option<Node>>,
+    #[serde(default, skip_serializing_if = "Keyframes::is_empty")]
+    pub keyframes: Keyframes,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bypass: bool,
+    #[serde(default, skip_serializing_if = "Label::is_empty")]
+    pub label: Label,
+}
+
+impl Node {
+    pub fn new(name: String, modifier: &(impl Modifier + ?Sized), keyframes: &Keyframes) -> Self {
+        Self {
+            modifier: name,
+            id: None,
+            params: params(modifier),
+            data: modifier.data(),
+            slots: modifier.slots().into_iter().map(Self::from_slot).collect(),
+            keyframes: keyframes.clone(),
+            bypass: false,
+            label: Label::default(),
+        }
+    }
+
+    pub fn from_cation(cation: &Cation<DynMod>) -> Self {
+        Self {
+            id: Some(cation.id),
+            bypass: cation.bypass,
+            label: cation.label.clone(),
+            ..Self::new(
+                cation.modifier.index.name.clone(),
+                &cation.modifier,
+                &cation.keyframes,
+            )
+        }
+    }
+
+    pub fn from_slot(slot: &ModifierSlot) -> Option<Self> {
+        slot.mod_ref().map(Self::from_cation)
+    }
+
+    /// Recreates the modifier, looking it up by name among the built-in modifiers and plugins.
+    pub fn into_cation(self) -> Result<Cation<DynMod>, String> {
+        self.into_cation_with(&available_modifiers())
+    }
+
+    pub fn into_cation_with(self, index: &[ModifierIndex]) -> Result<Cation<DynMod>, String> {
+        let found = index
+            .iter()
+            .find(|index| index.name == self.modifier)
+            .ok_or_else(|| format!("unknown modifier `{}`", self.modifier))?;
+
+        let mut modifier = DynMod::from_index(found.clone());
+
+        let slots = self
+            .slots
+            .into_iter()
+            .map(|node| {
+                node.map_or(Ok(ModifierSlot::Empty), |node| {
+                    Ok(ModifierSlot::from_cacher(node.into_cation_with(index)?))
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // slots first, as parameters can refer to them
+        modifier.set_slot_count(slots.len());
+        for (slot, loaded) in modifier.slots_mut().into_iter().zip(slots) {
+            *slot = loaded;
+        }
+
+        set_params(&mut modifier, self.params)
+            .map_err(|err| format!("{}: {err}", self.modifier))?;
+        if let Some(data) = self.data {
+            modifier
+                .set_data(data)
+                .map_err(|err| format!("{}: {err}", self.modifier))?;
+        }
+
+        let mut cation = Cation::new(modifier);
+        cation.keyframes = self.keyframes;
+        cation.bypass = self.bypass;
+        cation.label = self.label;
+        if let Some(id) = self.id {
+            cation.id = id;
+        }
+        Ok(cation)
+    }
+}
+
+fn serialize_data<S: Serializer>(
+    data: &Option<Arc<[u8]>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match data {
+        Some(data) => serializer.serialize_str(&STANDARD.encode(data)),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn deserialize_data<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Arc<[u8]>>, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    STANDARD
+        .decode(text)
+        .map(|data| Some(data.into()))
+        .map_err(D::Error::custom)
+}
+
+/// The values of all parameters that are not slots.
+pub fn params(modifier: &(impl Modifier + ?Sized)) -> BTreeMap<String, Value> {
+    modifier
+        .params()
+        .into_iter()
+        .filter(|param| param.kind != ParamKind::Slot)
+        .filter_map(|param| Some((param.name.to_string(), modifier.get_param(param.name)?)))
+        .collect()
+}
+
+/// Sets the parameters in the order the modifier declares them, as some depend on others.
+pub fn set_params(
+    modifier: &mut (impl Modifier + ?Sized),
+    mut params: BTreeMap<String, Value>,
+) -> Result<(), String> {
+    for param in modifier.params() {
+        if let Some(value) = params.remove(param.name) {
+            modifier
+                .set_param(param.name, value)
+                .map_err(|err| format!("{}: {err}", param.name))?;
+        }
+    }
+
+    match params.keys().next() {
+        Some(name) => Err(format!("no parameter `{name}`")),
+        None => Ok(()),
+    }
+}
@@ -1,10 +1,20 @@
-use std::any::{type_name, Any, TypeId};
+use std::{
+    any::{type_name, Any, TypeId},
+    sync::Arc,
+};
 
 use dyn_clone::DynClone;
+#[cfg(feature = "gui")]
 use eframe::egui::Ui;
 
-use super::cation::Output;
+use super::{
+    cation::Output,
+    metadata::Metadata,
+    param::{Param, ParamKind, Value, UNKNOWN},
+};
+#[cfg(feature = "gui")]
 use crate::editor::Editor;
+use crate::slot::ModifierSlot;
 
 pub trait Modifier: DynClone + DynPartialEq {
     fn apply(&mut self, input: &mut Output);
@@ -20,6 +30,14 @@ pub trait Modifier: DynClone + DynPartialEq {
             .concat()
     }
 
+    /// The category, icon, description and keywords shown when adding the modifier.
+    fn metadata() -> Metadata
+    where
+        Self: Sized,
+    {
+        Metadata::default()
+    }
+
     fn index() -> ModifierIndex
     where
         Self: Sized + Default + 'static,
@@ -28,15 +46,73 @@ pub trait Modifier: DynClone + DynPartialEq {
             name: Self::name(),
             id: TypeId::of::<Self>(),
             instancer: Box::new(|| Box::<Self>::default()),
+            metadata: Self::metadata(),
         }
     }
 
+    #[cfg(feature = "gui")]
+    fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {
+        super::param::view(self, ui, editor);
+    }
+
+    fn params(&self) -> Vec<Param> {
+        Vec::new()
+    }
+
+    #[allow(unused_variables)]
+    fn get_param(&self, name: &str) -> Option<Value> {
+        None
+    }
+
+    #[allow(unused_variables)]
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        Err(UNKNOWN)
+    }
+
+    /// All nested slots, starting with the [`ParamKind::Slot`] parameters in the same order.
+    fn slots(&self) -> Vec<&ModifierSlot> {
+        Vec::new()
+    }
+
+    fn slots_mut(&mut self) -> Vec<&mut ModifierSlot> {
+        Vec::new()
+    }
+
+    /// Makes room for the given number of slots, for modifiers like a list whose number of
+    /// slots varies.
+    #[allow(unused_variables)]
+    fn set_slot_count(&mut self, count: usize) {}
+
+    /// Bytes kept in project files next to the parameters, like an embedded image.
+    fn data(&self) -> Option<Arc<[u8]>> {
+        None
+    }
+
     #[allow(unused_variables)]
-    fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {}
+    fn set_data(&mut self, data: Arc<[u8]>) -> Result<(), &'static str> {
+        Err("modifier holds no data")
+    }
+
+    fn slot(&self, name: &str) -> Option<&ModifierSlot> {
+        let index = slot_index(&self.params(), name)?;
+        self.slots().into_iter().nth(index)
+    }
+
+    fn slot_mut(&mut self, name: &str) -> Option<&mut ModifierSlot> {
+        let index = slot_index(&self.params(), name)?;
+        self.slots_mut().into_iter().nth(index)
+    }
 }
 
 dyn_clone::clone_trait_object!(Modifier);
 
+fn slot_index(params: &[Param], name: &str) -> Option<usize> {
+    params
+        .iter()
+        .filter(|param| param.kind == ParamKind::Slot)
+        .position(|param| param.name == name)
+}
+
 pub trait DynPartialEq {
     fn eq(&self, other: &dyn DynPartialEq) -> bool;
     fn as_any(&self) -> &dyn Any;
@@ -79,10 +155,12 @@ pub struct ModifierIndex {
     pub name: String,
     pub id: TypeId,
     pub instancer: Box<dyn ModInstancer>,
+    pub metadata: Metadata,
 }
 
 impl PartialEq for ModifierIndex {
+    /// Macros and plugins share a type each, so they are told apart by name.
     fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+        self.id == other.id && self.name == other.name
     }
 }
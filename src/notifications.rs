@@ -0,0 +1,47 @@
+use eframe::egui::{self, Align2, Context};
+
+/// How many messages are kept, the oldest are dropped first.
+const LIMIT: usize = 5;
+
+/// Errors shown in the corner of the window until they are dismissed, for failures that happen
+/// outside of any dialog like saving a file in the background.
+#[derive(Default)]
+pub struct Notifications {
+    messages: Vec<String>,
+}
+
+impl Notifications {
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.messages.push(message.into());
+        if self.messages.len() > LIMIT {
+            self.messages.remove(0);
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if self.messages.is_empty() {
+            return;
+        }
+
+        let mut dismissed = None;
+
+        egui::Area::new("notifications")
+            .anchor(Align2::RIGHT_BOTTOM, [-8.0, -40.0])
+            .show(ctx, |ui| {
+                for (i, message) in self.messages.iter().enumerate() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(ui.visuals().error_fg_color, message);
+                            if ui.small_button("✖").on_hover_text("dismiss").clicked() {
+                                dismissed = Some(i);
+                            }
+                        });
+                    });
+                }
+            });
+
+        if let Some(i) = dismissed {
+            self.messages.remove(i);
+        }
+    }
+}
@@ -0,0 +1,550 @@
+//! Fuzzy search over everything the editor can do, run from the keyboard.
+
+use std::{cmp::Reverse, path::PathBuf};
+
+use eframe::egui::{self, Color32, Context, Key, Ui};
+
+use crate::{
+    editor::Editor,
+    keymap::{self, Action},
+    modifier::{
+        cation::{Cation, DynMod},
+        metadata::Category,
+        traits::ModifierIndex,
+    },
+    presets::Preset,
+    project::Project,
+    view::{Background, Display},
+};
+
+/// What the palette lists.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Scope {
+    /// Every action, menu item, modifier, preset and recent file.
+    Commands,
+    /// Modifiers, presets and recent files.
+    QuickOpen,
+    /// Modifiers and presets.
+    Modifiers,
+}
+
+#[derive(Default)]
+pub struct Palette {
+    pub scope: Option<Scope>,
+    query: String,
+    highlighted: usize,
+}
+
+impl Palette {
+    pub fn open(&mut self, scope: Scope) {
+        self.scope = Some(scope);
+        self.query.clear();
+        self.highlighted = 0;
+    }
+}
+
+/// A menu item without a shortcut.
+type MenuItem = fn(&mut Editor, &mut Project);
+
+enum Command {
+    Action(Action),
+    Run(MenuItem),
+    Add(ModifierIndex),
+    Preset(Preset),
+    Open(PathBuf),
+}
+
+struct Entry {
+    label: String,
+    hint: String,
+    tooltip: String,
+    command: Command,
+}
+
+impl Entry {
+    fn new(label: impl Into<String>, hint: impl Into<String>, command: Command) -> Self {
+        Self {
+            label: label.into(),
+            hint: hint.into(),
+            tooltip: String::new(),
+            command,
+        }
+    }
+}
+
+/// Scores how well the query matches the text, or `None` if its letters are not all in the
+/// text in order. Letters starting words and following each other score higher, gaps and
+/// longer texts lower.
+pub fn score(query: &str, text: &str) -> Option<i32> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let lower = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let starts_word = |i: usize| {
+        i == 0
+            || !chars[i - 1].is_alphanumeric()
+            || (chars[i].is_uppercase() && chars[i - 1].is_lowercase())
+    };
+
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.chars().filter(|c| !c.is_whitespace()).map(lower) {
+        let found = next + chars[next..].iter().position(|c| lower(*c) == wanted)?;
+
+        score += 1;
+        if starts_word(found) {
+            score += 8;
+        }
+        match previous {
+            Some(previous) if previous + 1 == found => score += 5,
+            Some(previous) => score -= (found - previous - 1).min(3) as i32,
+            None => score -= found.min(3) as i32,
+        }
+
+        previous = Some(found);
+        next = found + 1;
+    }
+
+    // shorter texts first when they match equally well
+    Some(score * 64 - chars.len().min(63) as i32)
+}
+
+/// Scores a modifier by its name, a bit lower by its keywords and lowest if only its
+/// description contains the query.
+pub fn score_index(query: &str, index: &ModifierIndex) -> Option<i32> {
+    let metadata = &index.metadata;
+    let keyword = metadata
+        .keywords
+        .iter()
+        .filter_map(|keyword| score(query, keyword))
+        .max()
+        .map(|score| score - 256);
+
+    score(query, &index.name).max(keyword).or_else(|| {
+        let query = query.trim().to_lowercase();
+        metadata
+            .description
+            .to_lowercase()
+            .contains(&query)
+            .then_some(i32::MIN / 2)
+    })
+}
+
+/// The items with a score, best first. All items in their order for an empty query.
+pub fn rank_by<T>(
+    query: &str,
+    items: impl IntoIterator<Item = T>,
+    score: impl Fn(&T) -> Option<i32>,
+) -> Vec<T> {
+    let mut scored = items
+        .into_iter()
+        .filter_map(|item| Some((score(&item)?, item)))
+        .collect::<Vec<_>>();
+
+    if !query.trim().is_empty() {
+        scored.sort_by_key(|(score, _)| Reverse(*score));
+    }
+
+    scored.into_iter().map(|(_, item)| item).collect()
+}
+
+/// The items whose text matches the query, best first.
+pub fn rank<T>(
+    query: &str,
+    items: impl IntoIterator<Item = T>,
+    text: impl Fn(&T) -> String,
+) -> Vec<T> {
+    rank_by(query, items, |item| score(query, &text(item)))
+}
+
+/// The modifiers to add, ranked by the query. Without one they are grouped under the
+/// favourites, the recently added ones and their categories.
+pub fn modifier_items(query: &str, editor: &Editor) -> Vec<(ModifierIndex, Item)> {
+    let available = editor
+        .index
+        .iter()
+        .chain(&editor.macros)
+        .collect::<Vec<_>>();
+    let item = |index: &ModifierIndex, heading: Option<&str>| Item {
+        label: modifier_label(index),
+        tooltip: index.metadata.description.clone(),
+        heading: heading.map(str::to_string),
+        favourite: Some(editor.usage.is_favourite(&index.name)),
+        ..Default::default()
+    };
+
+    if !query.trim().is_empty() {
+        return rank_by(query, available, |index| score_index(query, index))
+            .into_iter()
+            .map(|index| (index.clone(), item(index, None)))
+            .collect();
+    }
+
+    let named = |names: &[String]| {
+        names
+            .iter()
+            .filter_map(|name| available.iter().find(|index| index.name == *name).copied())
+            .collect::<Vec<_>>()
+    };
+
+    let mut sections = vec![
+        ("★ favourites", named(editor.usage.favourites())),
+        ("🕘 recent", named(editor.usage.recent())),
+    ];
+    for category in Category::ALL {
+        let indices = available
+            .iter()
+            .filter(|index| index.metadata.category == category)
+            .copied()
+            .collect();
+        sections.push((category.name(), indices));
+    }
+
+    sections
+        .into_iter()
+        .flat_map(|(heading, indices)| {
+            indices
+                .into_iter()
+                .enumerate()
+                .map(move |(i, index)| (index.clone(), item(index, (i == 0).then_some(heading))))
+        })
+        .collect()
+}
+
+pub fn modifier_label(index: &ModifierIndex) -> String {
+    format!("{} {}", index.metadata.icon, index.name)
+        .trim()
+        .to_string()
+}
+
+/// A row of [`list`].
+#[derive(Default)]
+pub struct Item {
+    pub label: String,
+    /// Shown on the right, like a shortcut.
+    pub hint: String,
+    pub tooltip: String,
+    /// Starts a section with this heading.
+    pub heading: Option<String>,
+    /// Shows a star to mark the item as a favourite, filled if it is one.
+    pub favourite: Option<bool>,
+}
+
+/// What happened to an item of a [`list`].
+pub enum Event {
+    Picked(usize),
+    Favourite(usize),
+    Hovered(usize),
+}
+
+/// Buttons for the items with the highlighted one moved by the arrow keys. An item is picked by
+/// clicking it or pressing enter while it is highlighted.
+pub fn list(
+    ui: &mut Ui,
+    items: impl IntoIterator<Item = Item>,
+    highlighted: &mut usize,
+) -> Option<Event> {
+    let items = items.into_iter().collect::<Vec<_>>();
+    if items.is_empty() {
+        ui.weak("nothing found");
+        return None;
+    }
+
+    let (up, down, enter) = ui.input(|input| {
+        (
+            input.key_pressed(Key::ArrowUp),
+            input.key_pressed(Key::ArrowDown),
+            input.key_pressed(Key::Enter),
+        )
+    });
+    if up {
+        *highlighted = highlighted.saturating_sub(1);
+    }
+    if down {
+        *highlighted += 1;
+    }
+    *highlighted = (*highlighted).min(items.len() - 1);
+
+    let mut event = enter.then_some(Event::Picked(*highlighted));
+    let mut hovered = None;
+
+    for (i, item) in items.into_iter().enumerate() {
+        if let Some(heading) = &item.heading {
+            ui.add_space(4.0);
+            ui.weak(heading);
+        }
+
+        ui.horizontal(|ui| {
+            if let Some(favourite) = item.favourite {
+                let star = ui
+                    .small_button(if favourite { "★" } else { "☆" })
+                    .on_hover_text("favourite");
+                if star.clicked() {
+                    event = Some(Event::Favourite(i));
+                }
+            }
+
+            let button = egui::Button::new(&item.label)
+                .shortcut_text(&item.hint)
+                .selected(i == *highlighted)
+                .min_size(egui::vec2(ui.available_width(), 0.0));
+            let mut response = ui.add(button);
+            if !item.tooltip.is_empty() {
+                response = response.on_hover_text(&item.tooltip);
+            }
+
+            if i == *highlighted && (up || down) {
+                response.scroll_to_me(None);
+            }
+            if response.clicked() {
+                event = Some(Event::Picked(i));
+            }
+            if response.hovered() {
+                hovered = Some(i);
+            }
+        });
+    }
+
+    event.or(hovered.map(Event::Hovered))
+}
+
+/// The palette window, running the picked entry.
+pub fn view(ctx: &Context, editor: &mut Editor, project: &mut Project) {
+    let Some(scope) = editor.palette.scope else {
+        return;
+    };
+
+    let mut entries = entries(scope, editor);
+    let mut picked = None;
+    let mut open = true;
+
+    let title = match scope {
+        Scope::Commands => "command palette",
+        Scope::QuickOpen => "quick open",
+        Scope::Modifiers => "add modifier",
+    };
+
+    egui::Window::new(title)
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .fixed_size([320.0, 0.0])
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+        .show(ctx, |ui| {
+            let palette = &mut editor.palette;
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut palette.query)
+                    .hint_text("type to search")
+                    .desired_width(f32::INFINITY),
+            );
+            response.request_focus();
+            if response.changed() {
+                palette.highlighted = 0;
+            }
+
+            let query = &palette.query;
+            let matches = rank_by(query, 0..entries.len(), |i| match &entries[*i].command {
+                Command::Add(index) => score_index(query, index),
+                _ => score(query, &entries[*i].label),
+            });
+
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    let items = matches.iter().map(|i| {
+                        let entry = &entries[*i];
+                        Item {
+                            label: entry.label.clone(),
+                            hint: entry.hint.clone(),
+                            tooltip: entry.tooltip.clone(),
+                            ..Default::default()
+                        }
+                    });
+                    if let Some(Event::Picked(i)) = list(ui, items, &mut palette.highlighted) {
+                        picked = Some(matches[i]);
+                    }
+                });
+        });
+
+    let cancelled = ctx.input(|input| input.key_pressed(Key::Escape));
+    if !open || cancelled || picked.is_some() {
+        editor.palette.scope = None;
+    }
+
+    if let Some(i) = picked {
+        run(entries.swap_remove(i).command, ctx, editor, project);
+    }
+}
+
+fn entries(scope: Scope, editor: &Editor) -> Vec<Entry> {
+    let mut entries = Vec::new();
+
+    if scope == Scope::Commands {
+        for action in Action::ALL {
+            entries.push(Entry::new(
+                action.name(),
+                editor.keymap.format(action),
+                Command::Action(action),
+            ));
+        }
+
+        let menu: [(&str, MenuItem); 13] = [
+            ("toggle pixel grid", |editor, _| {
+                editor.view.show_grid ^= true
+            }),
+            ("toggle rulers", |editor, _| editor.view.show_rulers ^= true),
+            ("toggle navigator", |editor, _| {
+                editor.view.show_navigator ^= true
+            }),
+            ("toggle profiler", |editor, _| editor.profiler.open ^= true),
+            ("toggle timeline", |editor, _| editor.timeline.open ^= true),
+            ("show color", |editor, _| {
+                editor.view.set_display(Display::Color)
+            }),
+            ("show alpha only", |editor, _| {
+                editor.view.set_display(Display::AlphaOnly)
+            }),
+            ("checkerboard background", |editor, _| {
+                editor.view.background = Background::Checkerboard
+            }),
+            ("black background", |editor, _| {
+                editor.view.background = Background::Solid(Color32::BLACK)
+            }),
+            ("white background", |editor, _| {
+                editor.view.background = Background::Solid(Color32::WHITE)
+            }),
+            ("clear guides", |_, project| project.guides.lines.clear()),
+            ("import presets", |editor, _| {
+                editor.picker.menu_import_presets().ok();
+            }),
+            ("export presets", |editor, _| {
+                editor.picker.menu_export_presets().ok();
+            }),
+        ];
+        for (label, run) in menu {
+            entries.push(Entry::new(label, "", Command::Run(run)));
+        }
+    }
+
+    for index in editor.index.iter().chain(&editor.macros) {
+        let mut entry = Entry::new(
+            format!("add {}", modifier_label(index)),
+            index.metadata.category.name(),
+            Command::Add(index.clone()),
+        );
+        entry.tooltip = index.metadata.description.clone();
+        entries.push(entry);
+    }
+
+    for preset in editor.presets.all() {
+        entries.push(Entry::new(
+            format!("{} preset: {}", preset.modifier, preset.name),
+            "preset",
+            Command::Preset(preset.clone()),
+        ));
+    }
+
+    if scope != Scope::Modifiers {
+        for path in editor.picker.recent() {
+            entries.push(Entry::new(
+                format!("open {}", path.display()),
+                "recent",
+                Command::Open(path.clone()),
+            ));
+        }
+    }
+
+    entries
+}
+
+fn run(command: Command, ctx: &Context, editor: &mut Editor, project: &mut Project) {
+    match command {
+        Command::Action(action) => keymap::perform(action, ctx, editor, project),
+        Command::Run(run) => run(editor, project),
+        Command::Add(index) => {
+            if let Err(err) = editor.usage.used(&index.name) {
+                editor
+                    .notifications
+                    .error(format!("failed to save modifier usage: {err}"));
+            }
+            editor.insert_after_selected(Cation::new(DynMod::from_index(index)));
+        }
+        Command::Preset(preset) => apply_preset(&preset, editor, project),
+        Command::Open(path) => editor.open(path, project),
+    }
+}
+
+/// Applies the preset to the selected modifier if it is of the preset's type, or adds a new
+/// modifier with it otherwise.
+fn apply_preset(preset: &Preset, editor: &mut Editor, project: &mut Project) {
+    let selected = editor.selected_id().filter(|id| {
+        project
+            .find(*id)
+            .is_some_and(|cation| cation.modifier.index.name == preset.modifier)
+    });
+
+    let result = match selected {
+        Some(id) => {
+            let mut result = Ok(());
+            project.root.for_each_descendant_mut(&mut |cation| {
+                if cation.id == id {
+                    result = preset.apply(&mut cation.modifier);
+                }
+            });
+            result
+        }
+        None => {
+            let index = editor
+                .index
+                .iter()
+                .chain(&editor.macros)
+                .find(|index| index.name == preset.modifier)
+                .cloned();
+
+            match index {
+                Some(index) => {
+                    let mut cation = Cation::new(DynMod::from_index(index));
+                    let result = preset.apply(&mut cation.modifier);
+                    editor.insert_after_selected(cation);
+                    result
+                }
+                None => Err(format!("no modifier called {}", preset.modifier)),
+            }
+        }
+    };
+
+    if let Err(err) = result {
+        editor
+            .notifications
+            .error(format!("failed to apply preset: {err}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_ranks_word_starts_and_runs_first() {
+        let names = ["ColorBalance", "BoxBlur", "Invert", "Blur"];
+        let ranked = rank_by("bl", names, |name| score("bl", name));
+        assert_eq!(ranked, ["Blur", "BoxBlur", "ColorBalance"]);
+    }
+
+    #[test]
+    fn score_needs_letters_in_order() {
+        assert_eq!(score("lb", "Blur"), None);
+        assert_eq!(score("x", "Blur"), None);
+        assert!(score("BLUR", "blur").is_some());
+        assert!(score("gb", "GaussianBlur") > score("gb", "Gaussblur"));
+        assert!(score("blur", "Blur") > score("blur", "Blurry"));
+    }
+
+    #[test]
+    fn empty_query_keeps_order() {
+        let names = ["b", "a", "c"];
+        assert_eq!(rank_by("", names, |name| score("", name)), names);
+    }
+}
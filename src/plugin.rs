@@ -0,0 +1,495 @@
+//! Modifiers loaded from WebAssembly modules in the plugins directory.
+//!
+//! A plugin exports its `memory` and three functions:
+//! - `alloc(len: i32) -> i32` returns a pointer to `len` free bytes.
+//! - `describe() -> i32` returns a pointer to a little-endian `u32` length followed by that many
+//!   bytes of JSON, like `{"name": "Posterize", "params": [{"kind": "int", "name": "levels",
+//!   "min": 2, "max": 32, "default": 4}]}`. Parameter kinds are `float`, `int` (both with `min`,
+//!   `max` and `default`), `bool` (with `default`) and `enum` (with `variants` and the `default`
+//!   index). It can also give a `category` (`adjust`, `filter`, `transform`, `draw`,
+//!   `composite` or `source`), an `icon`, a `description` and `keywords` for the add menu.
+//! - `apply(pixels: i32, width: i32, height: i32, params: i32) -> i32` modifies `width * height`
+//!   RGBA8 pixels in place and returns 0 on success. `params` points to one `f32` per parameter,
+//!   where booleans are 0 or 1 and enums the index of the variant.
+//!
+//! Every apply runs in a fresh instance with limited fuel and memory, so a plugin that traps,
+//! loops or misbehaves only fails its own step.
+
+use std::{
+    any::TypeId,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
+
+#[cfg(feature = "gui")]
+use eframe::egui::Ui;
+use serde::Deserialize;
+use wasmi::{
+    Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+#[cfg(feature = "gui")]
+use crate::editor::Editor;
+use crate::modifier::{
+    cation::Output,
+    collection::modifiers_collection,
+    metadata::{Category, Metadata},
+    param::{self, Param, ParamKind, Value, UNKNOWN, WRONG_TYPE},
+    traits::{Modifier, ModifierIndex},
+};
+
+const MEMORY_LIMIT: usize = 512 * 1024 * 1024;
+const FUEL_PER_PIXEL: u64 = 2_000;
+const FUEL_BASE: u64 = 10_000_000;
+
+static PLUGINS: OnceLock<Vec<Arc<Plugin>>> = OnceLock::new();
+
+pub struct Plugin {
+    pub name: String,
+    pub path: PathBuf,
+    metadata: Metadata,
+    params: Vec<(Param, Value)>,
+    engine: Engine,
+    module: Module,
+}
+
+/// A step running a plugin.
+#[derive(Clone)]
+pub struct PluginMod {
+    plugin: Arc<Plugin>,
+    values: Vec<Value>,
+    /// Why the last apply failed, in which case the input is passed on unchanged.
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Descriptor {
+    name: String,
+    #[serde(default)]
+    params: Vec<ParamDescriptor>,
+    #[serde(default)]
+    category: Category,
+    #[serde(default = "default_icon")]
+    icon: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    keywords: Vec<String>,
+}
+
+fn default_icon() -> String {
+    "⚡".to_string()
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum ParamDescriptor {
+    Float {
+        name: String,
+        min: f32,
+        max: f32,
+        #[serde(default)]
+        default: f32,
+    },
+    Int {
+        name: String,
+        min: i32,
+        max: i32,
+        #[serde(default)]
+        default: i32,
+    },
+    Bool {
+        name: String,
+        #[serde(default)]
+        default: bool,
+    },
+    Enum {
+        name: String,
+        variants: Vec<String>,
+        #[serde(default)]
+        default: usize,
+    },
+}
+
+/// The directory plugins are loaded from, `PLUGINS_PATH` or `plugins` in the config directory.
+pub fn plugins_dir() -> Option<PathBuf> {
+    std::env::var_os("PLUGINS_PATH")
+        .map(PathBuf::from)
+        .or_else(|| {
+            Some(
+                dirs::config_dir()?
+                    .join(env!("CARGO_PKG_NAME"))
+                    .join("plugins"),
+            )
+        })
+}
+
+/// Loads every `.wasm` file in the plugins directory, once at startup. Plugins that fail to load
+/// are skipped and reported.
+pub fn load_plugins() -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut plugins: Vec<Arc<Plugin>> = Vec::new();
+
+    let entries = plugins_dir()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "wasm")
+        });
+
+    for path in entries {
+        match Plugin::load(&path) {
+            Ok(plugin) if plugins.iter().any(|other| other.name == plugin.name) => errors.push(
+                format!("{}: `{}` is loaded twice", path.display(), plugin.name),
+            ),
+            Ok(plugin) => plugins.push(Arc::new(plugin)),
+            Err(err) => errors.push(format!("{}: {err}", path.display())),
+        }
+    }
+
+    if PLUGINS.set(plugins).is_err() {
+        errors.push("plugins are already loaded".to_string());
+    }
+
+    errors
+}
+
+/// Index entries of the loaded plugins.
+pub fn plugins_collection() -> Vec<ModifierIndex> {
+    PLUGINS
+        .get()
+        .into_iter()
+        .flatten()
+        .map(|plugin| plugin.index())
+        .collect()
+}
+
+impl Plugin {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+
+        let bytes = fs::read(path).map_err(|err| err.to_string())?;
+        let module = Module::new(&engine, &bytes[..]).map_err(|err| err.to_string())?;
+
+        let mut plugin = Self {
+            name: String::new(),
+            path: path.to_path_buf(),
+            metadata: Metadata::default(),
+            params: Vec::new(),
+            engine,
+            module,
+        };
+
+        let descriptor: Descriptor =
+            serde_json::from_slice(&plugin.describe()?).map_err(|err| err.to_string())?;
+
+        if modifiers_collection()
+            .iter()
+            .any(|index| index.name == descriptor.name)
+        {
+            return Err(format!("`{}` is a built-in modifier", descriptor.name));
+        }
+
+        plugin.name = descriptor.name;
+        plugin.metadata = Metadata {
+            category: descriptor.category,
+            icon: descriptor.icon,
+            description: descriptor.description,
+            keywords: descriptor.keywords,
+        };
+        plugin.params = descriptor
+            .params
+            .into_iter()
+            .map(ParamDescriptor::into_param)
+            .collect::<Result<_, _>>()?;
+
+        Ok(plugin)
+    }
+
+    fn index(self: &Arc<Self>) -> ModifierIndex {
+        let plugin = self.clone();
+        ModifierIndex {
+            name: self.name.clone(),
+            id: TypeId::of::<PluginMod>(),
+            instancer: Box::new(move || {
+                Box::new(PluginMod::new(plugin.clone())) as Box<dyn Modifier>
+            }),
+            metadata: self.metadata.clone(),
+        }
+    }
+
+    /// A fresh instance in its own store with the given fuel.
+    fn instantiate(&self, fuel: u64) -> Result<(Store<StoreLimits>, Instance, Memory), String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MEMORY_LIMIT)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.add_fuel(fuel).map_err(|err| err.to_string())?;
+
+        let instance = Linker::new(&self.engine)
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|err| err.to_string())?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or("no exported memory")?;
+
+        Ok((store, instance, memory))
+    }
+
+    fn describe(&self) -> Result<Vec<u8>, String> {
+        let (mut store, instance, memory) = self.instantiate(FUEL_BASE)?;
+        let describe = instance
+            .get_typed_func::<(), i32>(&store, "describe")
+            .map_err(|err| err.to_string())?;
+
+        let pointer = describe
+            .call(&mut store, ())
+            .map_err(|err| err.to_string())? as usize;
+
+        let mut length = [0; 4];
+        memory
+            .read(&store, pointer, &mut length)
+            .map_err(|err| err.to_string())?;
+
+        let length = u32::from_le_bytes(length) as usize;
+        if length > memory.data(&store).len() {
+            return Err("description is out of bounds".to_string());
+        }
+
+        let mut json = vec![0; length];
+        memory
+            .read(&store, pointer + 4, &mut json)
+            .map_err(|err| err.to_string())?;
+
+        Ok(json)
+    }
+
+    fn run(
+        &self,
+        pixels: &mut [u8],
+        width: u32,
+        height: u32,
+        values: &[f32],
+    ) -> Result<(), String> {
+        let length = i32::try_from(pixels.len())
+            .ok()
+            .filter(|length| *length as usize <= MEMORY_LIMIT)
+            .ok_or("image is too large for plugins")?;
+
+        let fuel = FUEL_BASE + FUEL_PER_PIXEL * (width as u64 * height as u64);
+        let (mut store, instance, memory) = self.instantiate(fuel)?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|err| err.to_string())?;
+        let apply = instance
+            .get_typed_func::<(i32, i32, i32, i32), i32>(&store, "apply")
+            .map_err(|err| err.to_string())?;
+
+        let params = values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect::<Vec<u8>>();
+
+        let pixels_pointer = alloc
+            .call(&mut store, length)
+            .map_err(|err| err.to_string())?;
+        let params_pointer = alloc
+            .call(&mut store, params.len().max(1) as i32)
+            .map_err(|err| err.to_string())?;
+
+        memory
+            .write(&mut store, pixels_pointer as usize, pixels)
+            .map_err(|err| err.to_string())?;
+        memory
+            .write(&mut store, params_pointer as usize, &params)
+            .map_err(|err| err.to_string())?;
+
+        let status = apply
+            .call(
+                &mut store,
+                (pixels_pointer, width as i32, height as i32, params_pointer),
+            )
+            .map_err(|err| err.to_string())?;
+        if status != 0 {
+            return Err(format!("plugin returned {status}"));
+        }
+
+        memory
+            .read(&store, pixels_pointer as usize, pixels)
+            .map_err(|err| err.to_string())
+    }
+}
+
+impl ParamDescriptor {
+    /// The parameter with its default value, or an error when its range or variants are empty or
+    /// it is not finite.
+    fn into_param(self) -> Result<(Param, Value), String> {
+        Ok(match self {
+            Self::Float {
+                name,
+                min,
+                max,
+                default,
+            } => {
+                if ![min, max, default].iter().all(|value| value.is_finite()) {
+                    return Err(format!("parameter `{name}` is not finite"));
+                }
+                if min > max {
+                    return Err(format!("parameter `{name}` has min above max"));
+                }
+                (
+                    Param::float(param::intern(&name), min..=max, (max - min) / 100.0),
+                    Value::Float(default.clamp(min, max)),
+                )
+            }
+            Self::Int {
+                name,
+                min,
+                max,
+                default,
+            } => {
+                if min > max {
+                    return Err(format!("parameter `{name}` has min above max"));
+                }
+                (
+                    Param::int(param::intern(&name), min..=max),
+                    Value::Int(default.clamp(min, max)),
+                )
+            }
+            Self::Bool { name, default } => (
+                Param::new(param::intern(&name), ParamKind::Bool),
+                Value::Bool(default),
+            ),
+            Self::Enum {
+                name,
+                variants,
+                default,
+            } => {
+                if variants.is_empty() {
+                    return Err(format!("parameter `{name}` has no variants"));
+                }
+                let last = variants.len() - 1;
+                (
+                    Param::new(
+                        param::intern(&name),
+                        ParamKind::Enum(param::intern_variants(&variants)),
+                    ),
+                    Value::Enum(default.min(last)),
+                )
+            }
+        })
+    }
+}
+
+impl PluginMod {
+    pub fn new(plugin: Arc<Plugin>) -> Self {
+        Self {
+            values: plugin
+                .params
+                .iter()
+                .map(|(_, value)| value.clone())
+                .collect(),
+            plugin,
+            error: None,
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.plugin
+            .params
+            .iter()
+            .position(|(param, _)| param.name == name)
+    }
+}
+
+impl PartialEq for PluginMod {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.plugin, &other.plugin) && self.values == other.values
+    }
+}
+
+impl Modifier for PluginMod {
+    fn apply(&mut self, input: &mut Output) {
+        let Some(image) = &mut input.image else {
+            return;
+        };
+
+        let values = self
+            .values
+            .iter()
+            .map(|value| match value {
+                Value::Bool(value) => *value as u8 as f32,
+                Value::Enum(value) => *value as f32,
+                value => value.as_f32().unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut pixels = image.as_rgba8().clone();
+        let (width, height) = pixels.dimensions();
+
+        self.error = self.plugin.run(&mut pixels, width, height, &values).err();
+
+        if self.error.is_none() {
+            *image.as_rgba8_mut() = pixels;
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    fn view(&mut self, ui: &mut Ui, editor: &mut Editor) {
+        param::view(self, ui, editor);
+
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+
+    fn params(&self) -> Vec<Param> {
+        self.plugin
+            .params
+            .iter()
+            .map(|(param, _)| param.clone())
+            .collect()
+    }
+
+    fn get_param(&self, name: &str) -> Option<Value> {
+        let index = self.position(name)?;
+        self.values.get(index).cloned()
+    }
+
+    fn set_param(&mut self, name: &str, value: Value) -> Result<(), &'static str> {
+        let index = self.position(name).ok_or(UNKNOWN)?;
+        let value = match (&self.plugin.params[index].0.kind, value) {
+            (ParamKind::Float { range, .. }, value) => Value::Float(
+                value
+                    .as_f32()
+                    .ok_or(WRONG_TYPE)?
+                    .clamp(*range.start(), *range.end()),
+            ),
+            (ParamKind::Int { range, .. }, value) => Value::Int(
+                value
+                    .as_i32()
+                    .ok_or(WRONG_TYPE)?
+                    .clamp(*range.start(), *range.end()),
+            ),
+            (ParamKind::Bool, value @ Value::Bool(_)) => value,
+            (ParamKind::Enum(variants), Value::Enum(variant)) if variant < variants.len() => {
+                Value::Enum(variant)
+            }
+            _ => return Err(WRONG_TYPE),
+        };
+
+        self.values[index] = value;
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
 use std::ops::{Add, AddAssign, Div, Mul, Sub};
 
+#[cfg(feature = "gui")]
 use eframe::egui::Vec2;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Position {
     pub x: f32,
     pub y: f32,
@@ -103,6 +105,7 @@ impl Sub for Position {
     }
 }
 
+#[cfg(feature = "gui")]
 impl From<Vec2> for Position {
     fn from(value: Vec2) -> Self {
         Self::new(value.x, value.y)
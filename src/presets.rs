@@ -0,0 +1,248 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "gui")]
+use eframe::egui::{self, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::modifier::{
+    cation::DynMod,
+    param::{ParamKind, Value},
+    save,
+    traits::Modifier,
+};
+#[cfg(feature = "gui")]
+use crate::notifications::Notifications;
+
+/// Named parameter values for one kind of modifier.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub modifier: String,
+    pub params: BTreeMap<String, Value>,
+}
+
+impl Preset {
+    pub fn new<'a, T: Modifier + Default + 'static>(
+        name: &str,
+        params: impl IntoIterator<Item = (&'a str, Value)>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            modifier: T::index().name,
+            params: params
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        }
+    }
+
+    /// Captures the current parameters, leaving out drawn points and other structured values.
+    pub fn capture(name: &str, modifier: &DynMod) -> Self {
+        let skipped = modifier
+            .params()
+            .into_iter()
+            .filter(|param| matches!(param.kind, ParamKind::Points | ParamKind::List))
+            .map(|param| param.name)
+            .collect::<Vec<_>>();
+
+        let mut params = save::params(modifier);
+        params.retain(|name, _| !skipped.contains(&name.as_str()));
+
+        Self {
+            name: name.to_string(),
+            modifier: modifier.index.name.clone(),
+            params,
+        }
+    }
+
+    pub fn apply(&self, modifier: &mut DynMod) -> Result<(), String> {
+        if modifier.index.name != self.modifier {
+            return Err(format!("preset is for {}", self.modifier));
+        }
+
+        save::set_params(modifier, self.params.clone())
+    }
+}
+
+/// The built-in presets and the ones saved by the user, which are kept in the config directory.
+#[derive(Default)]
+pub struct Presets {
+    builtin: Vec<Preset>,
+    user: Vec<Preset>,
+    #[cfg(feature = "gui")]
+    new_name: String,
+}
+
+impl Presets {
+    pub fn add_builtin(&mut self, preset: Preset) {
+        self.builtin.push(preset);
+    }
+
+    pub fn builtin(&self, modifier: &str) -> impl Iterator<Item = &Preset> {
+        let modifier = modifier.to_string();
+        self.builtin
+            .iter()
+            .filter(move |preset| preset.modifier == modifier)
+    }
+
+    pub fn user(&self, modifier: &str) -> impl Iterator<Item = &Preset> {
+        let modifier = modifier.to_string();
+        self.user
+            .iter()
+            .filter(move |preset| preset.modifier == modifier)
+    }
+
+    /// Every preset for every modifier, the built-in ones first.
+    pub fn all(&self) -> impl Iterator<Item = &Preset> {
+        self.builtin.iter().chain(&self.user)
+    }
+
+    pub fn user_path() -> Option<PathBuf> {
+        Some(
+            dirs::config_dir()?
+                .join(env!("CARGO_PKG_NAME"))
+                .join("presets.json"),
+        )
+    }
+
+    /// Loads the user's presets, if they saved any before.
+    pub fn load_user(&mut self) -> Result<(), String> {
+        let Some(path) = Self::user_path() else {
+            return Ok(());
+        };
+
+        if path.exists() {
+            self.user = read(&path)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn save_user(&self) -> Result<(), String> {
+        let path = Self::user_path().ok_or("no config directory")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+
+        write(&path, &self.user)
+    }
+
+    /// Adds a user preset, replacing the one with the same name for the same modifier.
+    pub fn save(&mut self, preset: Preset) -> Result<(), String> {
+        self.insert(preset);
+        self.save_user()
+    }
+
+    pub fn remove(&mut self, modifier: &str, name: &str) -> Result<(), String> {
+        self.user
+            .retain(|preset| preset.modifier != modifier || preset.name != name);
+
+        self.save_user()
+    }
+
+    /// Adds the presets from a shared file, returning how many there were.
+    pub fn import(&mut self, path: impl AsRef<Path>) -> Result<usize, String> {
+        let presets = read(path.as_ref())?;
+        let count = presets.len();
+
+        for preset in presets {
+            self.insert(preset);
+        }
+
+        self.save_user()?;
+        Ok(count)
+    }
+
+    fn insert(&mut self, preset: Preset) {
+        self.user
+            .retain(|other| other.modifier != preset.modifier || other.name != preset.name);
+        self.user.push(preset);
+        self.user
+            .sort_by(|a, b| (&a.modifier, &a.name).cmp(&(&b.modifier, &b.name)));
+    }
+
+    /// Writes all user presets to a file that can be shared.
+    pub fn export(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        write(path.as_ref(), &self.user)
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<Preset>, String> {
+    let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    serde_json::from_str(&text).map_err(|err| err.to_string())
+}
+
+fn write(path: &Path, presets: &[Preset]) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(presets).map_err(|err| err.to_string())?;
+    fs::write(path, text).map_err(|err| err.to_string())
+}
+
+#[cfg(feature = "gui")]
+impl Presets {
+    /// Menu in a slot header to apply, save and delete presets of the modifier.
+    pub fn menu(&mut self, ui: &mut Ui, modifier: &mut DynMod, notifications: &mut Notifications) {
+        ui.menu_button("☰", |ui| {
+            let name = modifier.index.name.clone();
+
+            let mut picked = None;
+            let mut removed = None;
+
+            for preset in self.builtin(&name) {
+                if ui.button(&preset.name).clicked() {
+                    picked = Some(preset.clone());
+                }
+            }
+
+            if self.user(&name).next().is_some() {
+                ui.separator();
+            }
+
+            for preset in self.user(&name) {
+                ui.horizontal(|ui| {
+                    if ui.button(&preset.name).clicked() {
+                        picked = Some(preset.clone());
+                    }
+                    if ui.small_button("🗑").clicked() {
+                        removed = Some(preset.name.clone());
+                    }
+                });
+            }
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.new_name).hint_text("name"));
+                if ui
+                    .add_enabled(!self.new_name.is_empty(), egui::Button::new("save"))
+                    .clicked()
+                {
+                    let preset = Preset::capture(&self.new_name, modifier);
+                    if let Err(err) = self.save(preset) {
+                        notifications.error(format!("failed to save preset: {err}"));
+                    }
+                    self.new_name.clear();
+                    ui.close_menu();
+                }
+            });
+
+            if let Some(preset) = picked {
+                if let Err(err) = preset.apply(modifier) {
+                    notifications.error(format!("failed to apply preset: {err}"));
+                }
+                ui.close_menu();
+            }
+
+            if let Some(preset) = removed {
+                if let Err(err) = self.remove(&name, &preset) {
+                    notifications.error(format!("failed to remove preset: {err}"));
+                }
+            }
+        })
+        .response
+        .on_hover_text("presets");
+    }
+}
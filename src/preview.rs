@@ -0,0 +1,108 @@
+//! Shows a modifier hovered in the add menu on the canvas before it is added.
+
+use eframe::egui::Context;
+use uuid::Uuid;
+
+use crate::{
+    image::Image,
+    modifier::{
+        cation::Cation,
+        collection::list::List,
+        traits::{Modifier, ModifierIndex},
+    },
+    project::Project,
+    slot::ModifierSlot,
+    view::View,
+};
+
+/// Where a previewed modifier would be added, by the id of the modifier it goes in.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Target {
+    /// The empty slot at the index among the slots of the parent.
+    Slot { parent: Uuid, index: usize },
+    /// The end of the list in the parent.
+    End(Uuid),
+}
+
+#[derive(Default)]
+pub struct Preview {
+    /// The modifier hovered in the add menu this frame, and where it would go.
+    pub hovered: Option<(ModifierIndex, Target)>,
+    /// The id of the modifier being drawn, for the slots in it to tell where they are.
+    pub parent: Option<Uuid>,
+    /// The name of the previewed modifier, where it goes, the revision of the project it was
+    /// applied to and the output with it, to only apply again when either changes.
+    shown: Option<(String, Target, u64, Option<Image>)>,
+}
+
+impl Preview {
+    /// Shows a copy of the project with the hovered modifier on the canvas, returning whether it
+    /// did. The project's own output is shown again once nothing is hovered.
+    pub fn show(&mut self, ctx: &Context, project: &Project, view: &mut View) -> bool {
+        let Some((index, target)) = self.hovered.take() else {
+            if self.shown.take().is_some() {
+                view.request_refresh();
+            }
+            return false;
+        };
+
+        let unchanged = self
+            .shown
+            .as_ref()
+            .is_some_and(|(name, shown, revision, _)| {
+                *name == index.name && *shown == target && *revision == project.revision()
+            });
+
+        if !unchanged {
+            let mut found = false;
+            let image = project.output_with(|root| {
+                found = insert(root, &index, target).is_some();
+            });
+            if !found {
+                if self.shown.take().is_some() {
+                    view.request_refresh();
+                }
+                return false;
+            }
+
+            self.shown = Some((index.name, target, project.revision(), image));
+            view.request_refresh();
+        }
+
+        if view.needs_refresh() {
+            if let Some((_, _, _, image)) = &self.shown {
+                view.update(ctx, image.as_ref().unwrap_or(&Image::default()));
+            }
+        }
+
+        true
+    }
+}
+
+fn insert(root: &mut Cation<List>, index: &ModifierIndex, target: Target) -> Option<()> {
+    let slot = ModifierSlot::from_index(index);
+
+    match target {
+        Target::Slot { parent, index } => {
+            let slots = if parent == root.id {
+                root.modifier.slots_mut()
+            } else {
+                root.find_mut(parent)?.modifier.slots_mut()
+            };
+            let empty = slots
+                .into_iter()
+                .nth(index)
+                .filter(|slot| slot.is_empty())?;
+            *empty = slot;
+        }
+        Target::End(parent) if parent == root.id => root.modifier.contents.push(slot),
+        Target::End(parent) => root
+            .find_mut(parent)?
+            .modifier
+            .modifier_mut::<List>()?
+            .contents
+            .push(slot),
+    }
+
+    Some(())
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use eframe::egui::{self, Context};
+
+use crate::{
+    modifier::cation::{Cation, DynMod},
+    project::Project,
+};
+
+#[derive(Default)]
+pub struct Profiler {
+    pub open: bool,
+    sort: Sort,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Sort {
+    #[default]
+    Last,
+    LastSelf,
+    Total,
+    TotalSelf,
+    Recomputes,
+    Memory,
+}
+
+impl Sort {
+    const ALL: [Self; 6] = [
+        Self::Last,
+        Self::LastSelf,
+        Self::Total,
+        Self::TotalSelf,
+        Self::Recomputes,
+        Self::Memory,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Last => "last",
+            Self::LastSelf => "last self",
+            Self::Total => "total",
+            Self::TotalSelf => "total self",
+            Self::Recomputes => "recomputes",
+            Self::Memory => "memory",
+        }
+    }
+}
+
+impl Profiler {
+    pub fn view(&mut self, ctx: &Context, project: &Project) {
+        let mut open = self.open;
+
+        egui::Window::new("profiler")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("sort by:");
+                    for sort in Sort::ALL {
+                        ui.selectable_value(&mut self.sort, sort, sort.name());
+                    }
+                });
+
+                ui.separator();
+
+                let mut cations = project.root.descendants();
+                cations.sort_by_key(|cation| std::cmp::Reverse(self.key(cation)));
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("profiler")
+                        .num_columns(8)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            let headers = [
+                                "step",
+                                "last",
+                                "last self",
+                                "total",
+                                "total self",
+                                "recomputes",
+                                "hits",
+                                "cache",
+                            ];
+                            for header in headers {
+                                ui.strong(header);
+                            }
+                            ui.end_row();
+
+                            for cation in cations {
+                                let stats = &cation.stats;
+                                ui.label(&cation.modifier.index.name);
+                                ui.monospace(format_duration(stats.last));
+                                ui.monospace(format_duration(stats.last_self));
+                                ui.monospace(format_duration(stats.total));
+                                ui.monospace(format_duration(stats.total_self));
+                                ui.monospace(stats.recomputes.to_string());
+                                ui.monospace(stats.hits.to_string());
+                                ui.monospace(format_bytes(cation.cache_memory()));
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        self.open = open;
+    }
+
+    fn key(&self, cation: &Cation<DynMod>) -> u128 {
+        match self.sort {
+            Sort::Last => cation.stats.last.as_micros(),
+            Sort::LastSelf => cation.stats.last_self.as_micros(),
+            Sort::Total => cation.stats.total.as_micros(),
+            Sort::TotalSelf => cation.stats.total_self.as_micros(),
+            Sort::Recomputes => cation.stats.recomputes.into(),
+            Sort::Memory => cation.cache_memory() as u128,
+        }
+    }
+}
+
+/// Summary of the stats, shown when hovering a slot header. Self times leave out the nested
+/// slots.
+pub fn summary(cation: &Cation<DynMod>) -> String {
+    let stats = &cation.stats;
+    format!(
+        "last: {} ({} self), total: {} ({} self)\nrecomputes: {}, cache hits: {}\ncache: {}",
+        format_duration(stats.last),
+        format_duration(stats.last_self),
+        format_duration(stats.total),
+        format_duration(stats.total_self),
+        stats.recomputes,
+        stats.hits,
+        format_bytes(cation.cache_memory())
+    )
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+pub fn format_bytes(bytes: usize) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
@@ -1,50 +1,399 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::{
+    export::{ExportSettings, Format},
+    guides::Guides,
     image::Image,
     modifier::{
-        cation::{Cation, Output},
-        collection::{list::List, source::Source},
+        cation::{Cation, DynMod, Output},
+        collection::{
+            available_modifiers,
+            generator::{canvas::Canvas, GeneratorMod},
+            list::List,
+            reference,
+            source::Source,
+        },
+        macros::{MacroDef, MacroFile, Macros},
+        save::Node,
+        traits::{Modifier, ModifierIndex},
     },
+    position::Position,
+    slot::ModifierSlot,
 };
 
+/// How often the project is applied at most for references to catch up with their targets.
+const MAX_PASSES: usize = 8;
+
 pub struct Project {
     pub root: Cation<List>,
+    pub guides: Guides,
+    pub macros: Macros,
+    /// The settings the output was last exported with.
+    pub export: ExportSettings,
+    time: f32,
+    /// Counts the changes to the project, see [`Project::update_revision`].
+    revision: u64,
+    /// The project as of the current revision, to notice changes without serialising it.
+    stored: Option<ProjectFile>,
+    /// The project as it was last saved or opened, to tell if it has unsaved changes.
+    saved: Option<ProjectFile>,
+    modified: bool,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct ProjectFile {
+    root: Node,
+    #[serde(default)]
+    guides: Guides,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    macros: Vec<MacroFile>,
+    #[serde(default, skip_serializing_if = "ExportSettings::is_default")]
+    export: ExportSettings,
 }
 
 impl Default for Project {
     fn default() -> Self {
         Self {
             root: Cation::new(List::default()),
+            guides: Guides::default(),
+            macros: Macros::default(),
+            export: ExportSettings::default(),
+            time: 0.0,
+            revision: 0,
+            stored: None,
+            saved: None,
+            modified: false,
         }
+        .mark_saved()
     }
 }
 
 impl Project {
     pub fn new_from_input_path(path: impl AsRef<Path>) -> Self {
+        Self::new_from_source(Source::new(path))
+    }
+
+    pub fn new_from_source(source: Source) -> Self {
         Self {
-            root: Cation::new(List::from_vec_mods(vec![Source::new(path)])),
+            root: Cation::new(List::from_vec_mods(vec![source])),
             ..Default::default()
         }
+        .mark_saved()
     }
 
+    /// A project starting from a white canvas of the given size.
+    pub fn new_blank(size: Position) -> Self {
+        Self {
+            root: Cation::new(List::from_vec_mods(vec![GeneratorMod::new(
+                size,
+                Canvas::default(),
+            )])),
+            ..Default::default()
+        }
+        .mark_saved()
+    }
+
+    /// Saves the output with the export settings, in the format of the path's extension when it
+    /// has a known one.
     pub fn export(&mut self, path: impl AsRef<Path>) -> Result<(), String> {
-        if let Some(output) = self.output() {
-            output.save(path).map_err(|err| err.to_string())
-        } else {
-            Err("no output to save".to_string())
+        let mut path = path.as_ref().to_path_buf();
+        match Format::from_path(&path) {
+            Some(format) => self.export.format = format,
+            None => {
+                path.set_extension(self.export.format.extensions()[0]);
+            }
+        }
+
+        match apply(&mut self.root) {
+            Some(output) => self.export.save(output, path),
+            None => Err("no output to save".to_string()),
         }
     }
 
     pub fn output(&mut self) -> &Option<Image> {
-        let input = Output::new_empty();
-        &self.root.output(&input).image
+        apply(&mut self.root)
+    }
+
+    /// The output of a copy of the project changed by `change`, leaving the project as it is. The
+    /// copy shares the cached outputs, so only the modifiers after the change are applied again.
+    pub fn output_with(&self, change: impl FnOnce(&mut Cation<List>)) -> Option<Image> {
+        let mut root = self.root.clone();
+        change(&mut root);
+        apply(&mut root).clone()
     }
 
     pub fn output_changed(&self) -> bool {
         !self.root.check_cache(&Output::new_empty())
     }
 
+    pub fn find(&self, id: Uuid) -> Option<&Cation<DynMod>> {
+        self.root.find(id)
+    }
+
+    pub fn save(&mut self, path: impl AsRef<Path>) -> Result<(), String> {
+        let json = self.to_json()?;
+        fs::write(path, json).map_err(|err| err.to_string())?;
+        self.update_revision();
+        self.saved = self.stored.clone();
+        self.modified = false;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        Ok(Self::from_json(&json)?.mark_saved())
+    }
+
+    /// The project as stored in a project file.
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(&self.file()).map_err(|err| err.to_string())
+    }
+
+    fn file(&self) -> ProjectFile {
+        ProjectFile {
+            root: Node::new(List::name(), &self.root.modifier, &self.root.keyframes),
+            guides: self.guides.clone(),
+            macros: self.macros.to_files(),
+            export: self.export.clone(),
+        }
+    }
+
+    /// Counts a new revision if the project changed since the last call. Compares the project
+    /// without serialising it, so it can be called every frame.
+    pub fn update_revision(&mut self) {
+        let file = self.file();
+        if self.stored.as_ref() != Some(&file) {
+            self.revision += 1;
+            self.modified = self.saved.as_ref() != Some(&file);
+            self.stored = Some(file);
+        }
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Replaces the modifiers, guides, macros and export settings with those stored in the json,
+    /// keeping the current time, for undoing changes.
+    pub fn restore(&mut self, json: &str) -> Result<(), String> {
+        let restored = Self::from_json(json)?;
+        self.root = restored.root;
+        self.guides = restored.guides;
+        self.macros = restored.macros;
+        self.export = restored.export;
+        self.set_time(self.time);
+        Ok(())
+    }
+
+    /// Whether the project changed since it was last saved or opened, as of the last
+    /// [`Project::update_revision`].
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    fn mark_saved(mut self) -> Self {
+        self.update_revision();
+        self.saved = self.stored.clone();
+        self.modified = false;
+        self
+    }
+
+    fn from_json(json: &str) -> Result<Self, String> {
+        let file: ProjectFile = serde_json::from_str(json).map_err(|err| err.to_string())?;
+
+        let macros = Macros::from_files(file.macros)?;
+        let mut index = available_modifiers();
+        index.extend(macros.indices());
+
+        let root = file.root.into_cation_with(&index)?;
+        let list = root
+            .modifier
+            .modifier::<List>()
+            .ok_or("project root is not a list")?
+            .clone();
+
+        let mut project = Self {
+            root: Cation::new(list),
+            guides: file.guides,
+            macros,
+            export: file.export,
+            time: 0.0,
+            revision: 0,
+            stored: None,
+            saved: None,
+            modified: false,
+        };
+        project.root.keyframes = root.keyframes;
+        project.set_time(0.0);
+
+        Ok(project)
+    }
+
+    /// The built-in modifiers, plugins and the project's macros.
+    pub fn index(&self) -> Vec<ModifierIndex> {
+        let mut index = available_modifiers();
+        index.extend(self.macros.indices());
+        index
+    }
+
+    /// Adds or replaces a macro, updating every instance of it.
+    pub fn define_macro(&mut self, def: Rc<MacroDef>) {
+        self.macros.define(def, self.root.modifier.slots_mut());
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Moves to a point in time, setting every keyframed parameter.
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+        self.root.animate(time);
+    }
+
+    /// Time of the last keyframe in the project.
+    pub fn duration(&self) -> f32 {
+        self.root
+            .descendants()
+            .into_iter()
+            .map(|cation| cation.keyframes.end())
+            .fold(self.root.keyframes.end(), f32::max)
+    }
+
+    /// Calls `f` for the root and every nested list, with the id of the modifier holding it.
+    fn for_each_list_mut(&mut self, f: &mut impl FnMut(Uuid, &mut List)) {
+        f(self.root.id, &mut self.root.modifier);
+        self.root.for_each_descendant_mut(&mut |cation| {
+            let id = cation.id;
+            if let Some(list) = cation.modifier.modifier_mut::<List>() {
+                f(id, list);
+            }
+        });
+    }
+
+    /// Moves the selected modifiers one place within their lists, towards the end if `later`.
+    pub fn move_selected(&mut self, selected: &HashSet<Uuid>, later: bool) {
+        self.for_each_list_mut(&mut |_, list| list.move_selected(selected, later));
+    }
+
+    pub fn delete(&mut self, selected: &HashSet<Uuid>) {
+        let clear = |slots: Vec<&mut ModifierSlot>| {
+            for slot in slots {
+                if slot
+                    .mod_ref()
+                    .is_some_and(|cation| selected.contains(&cation.id))
+                {
+                    *slot = ModifierSlot::Empty;
+                }
+            }
+        };
+
+        clear(self.root.modifier.slots_mut());
+        self.root
+            .for_each_descendant_mut(&mut |cation| clear(cation.modifier.slots_mut()));
+        self.for_each_list_mut(&mut |_, list| list.contents.retain(|slot| !slot.is_empty()));
+    }
+
+    pub fn set_bypass(&mut self, selected: &HashSet<Uuid>, bypass: bool) {
+        self.root.for_each_descendant_mut(&mut |cation| {
+            if selected.contains(&cation.id) {
+                cation.bypass = bypass;
+            }
+        });
+    }
+
+    /// Groups the selected modifiers into a nested list per list they are in, returning the ids
+    /// of the new lists.
+    pub fn group(&mut self, selected: &HashSet<Uuid>) -> Vec<Uuid> {
+        let contains_selected =
+            |list: &List| list.iter_mods().any(|cation| selected.contains(&cation.id));
+
+        let mut parents = HashSet::new();
+        if contains_selected(&self.root.modifier) {
+            parents.insert(self.root.id);
+        }
+        for cation in self.root.descendants() {
+            if cation
+                .modifier
+                .modifier::<List>()
+                .is_some_and(contains_selected)
+            {
+                parents.insert(cation.id);
+            }
+        }
+
+        // only the lists found before, not the new ones holding only selected modifiers
+        let mut groups = Vec::new();
+        self.for_each_list_mut(&mut |id, list| {
+            if parents.contains(&id) {
+                groups.extend(list.group(selected));
+            }
+        });
+        groups
+    }
+
+    /// Replaces the selected nested lists by their contents, returning the ids of those.
+    pub fn ungroup(&mut self, selected: &HashSet<Uuid>) -> Vec<Uuid> {
+        let mut ungrouped = Vec::new();
+        self.for_each_list_mut(&mut |_, list| ungrouped.extend(list.ungroup(selected)));
+        ungrouped
+    }
+
+    /// Applies an override like `blur.sigma=3` to every modifier of that type, returning how
+    /// many were changed.
+    pub fn override_param(&mut self, assignment: &str) -> Result<usize, String> {
+        let (target, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("expected `modifier.param=value`, got `{assignment}`"))?;
+        let (modifier, param) = target
+            .split_once('.')
+            .ok_or_else(|| format!("expected `modifier.param`, got `{target}`"))?;
+
+        let mut changed = 0;
+        let mut result = Ok(());
+
+        self.root.for_each_descendant_mut(&mut |cation| {
+            if result.is_err() || !matches_name(&cation.modifier.index.name, modifier) {
+                return;
+            }
+
+            let Some(kind) = cation
+                .modifier
+                .params()
+                .into_iter()
+                .find(|candidate| candidate.name == param)
+                .map(|param| param.kind)
+            else {
+                result = Err(format!("{modifier} has no parameter `{param}`"));
+                return;
+            };
+
+            result = kind.parse(value).and_then(|value| {
+                cation
+                    .modifier
+                    .set_param(param, value)
+                    .map_err(String::from)
+            });
+            changed += 1;
+        });
+
+        result?;
+
+        if changed == 0 {
+            Err(format!("no `{modifier}` modifier in the project"))
+        } else {
+            Ok(changed)
+        }
+    }
+
     pub fn path(&self) -> Option<PathBuf> {
         self.root
             .modifier
@@ -53,3 +402,94 @@ impl Project {
             .map(|source| source.path.clone())
     }
 }
+
+fn apply(root: &mut Cation<List>) -> &Option<Image> {
+    let input = Output::new_empty();
+
+    // nothing changed since the references were last resolved
+    if root.check_cache(&input) {
+        return &root.output(&input).image;
+    }
+
+    // references get the outputs of the previous pass, so apply until they are up to date
+    for _ in 0..MAX_PASSES {
+        root.output(&input);
+        if !reference::resolve(root) {
+            break;
+        }
+    }
+
+    &root.output(&input).image
+}
+
+/// Matches a modifier name case insensitively, also by its generic argument so `simplepencil`
+/// finds `PencilMod<SimplePencil>`.
+fn matches_name(name: &str, query: &str) -> bool {
+    name.eq_ignore_ascii_case(query)
+        || name
+            .split_once('<')
+            .is_some_and(|(_, inner)| inner.trim_end_matches('>').eq_ignore_ascii_case(query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        export::Format,
+        guides::Guide,
+        modifier::{
+            collection::{brighten::Brighten, contrast::Contrast, reference::Reference},
+            param::Value,
+        },
+    };
+
+    #[test]
+    fn json_round_trip() {
+        // the value the keyframes give at the start, as loading animates the project
+        let mut brighten = Cation::new(DynMod::new(Brighten { value: 0 }));
+        brighten.bypass = true;
+        brighten.label.name = "lighter".to_string();
+        brighten.keyframes.insert("amount", 0.0, Value::Int(0));
+        brighten.keyframes.insert("amount", 1.0, Value::Int(40));
+        let target = brighten.id;
+
+        let group = Cation::new(DynMod::new(List {
+            contents: vec![
+                ModifierSlot::from_mod(Contrast { value: 1.5 }),
+                ModifierSlot::Empty,
+                ModifierSlot::from_mod(Reference::new(target)),
+            ],
+        }));
+
+        let mut project = Project::default();
+        project.root.modifier.contents = vec![
+            ModifierSlot::from_mod(Source::new("input.png")),
+            ModifierSlot::from_cacher(brighten),
+            ModifierSlot::from_cacher(group),
+        ];
+        project.guides.lines.push(Guide::Vertical(12.5));
+        project.export.format = Format::Jpeg;
+        project.export.quality = 70;
+
+        let json = project.to_json().unwrap();
+        let mut loaded = Project::from_json(&json).unwrap();
+
+        assert_eq!(loaded.to_json().unwrap(), json);
+
+        loaded.set_time(1.0);
+        assert_eq!(
+            loaded.find(target).unwrap().modifier.get_param("amount"),
+            Some(Value::Int(40))
+        );
+        assert!(loaded.find(target).is_some_and(|cation| cation.bypass));
+        assert!(loaded.guides == project.guides);
+        assert_eq!(loaded.export, project.export);
+        assert_eq!(loaded.path(), Some(PathBuf::from("input.png")));
+    }
+
+    #[test]
+    fn unknown_modifier_is_an_error() {
+        let json = r#"{ "root": { "modifier": "List", "slots": [{ "modifier": "Nope" }] } }"#;
+        assert!(Project::from_json(json).is_err());
+    }
+}
@@ -1,15 +1,22 @@
+#[cfg(feature = "gui")]
 use eframe::egui::{
     self, style::Margin, Align2, Button, Color32, Frame, LayerId, Order, Rounding, Sense, Stroke,
-    TextStyle, Ui,
+    TextStyle, Ui, Vec2,
 };
 use uuid::Uuid;
 
+use crate::modifier::{
+    cation::{Cation, DynMod, Output},
+    traits::{Modifier, ModifierIndex},
+};
+#[cfg(feature = "gui")]
 use crate::{
+    clipboard,
     editor::Editor,
-    modifier::{
-        cation::{Cation, DynMod, Output},
-        traits::{Modifier, ModifierIndex},
-    },
+    modifier::{collection::list::List, keyframes, label, macros},
+    palette::{self, Event},
+    preview::Target,
+    profiler,
 };
 
 #[derive(Clone, PartialEq)]
@@ -69,6 +76,27 @@ impl ModifierSlot {
         }
     }
 
+    pub fn find(&self, id: Uuid) -> Option<&Cation<DynMod>> {
+        let cation = self.mod_ref()?;
+        if cation.id == id {
+            Some(cation)
+        } else {
+            cation.find(id)
+        }
+    }
+
+    pub fn find_mut(&mut self, id: Uuid) -> Option<&mut Cation<DynMod>> {
+        let cation = self.mod_mut()?;
+        if cation.id == id {
+            Some(cation)
+        } else {
+            cation.find_mut(id)
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl ModifierSlot {
     fn drag(&mut self) -> Option<Cation<DynMod>> {
         self.take(Self::Dragged(self.mod_ref()?.id))
     }
@@ -101,7 +129,9 @@ impl ModifierSlot {
     }
 
     fn view_modifier(&mut self, ui: &mut Ui, editor: &mut Editor, prefix: Option<&str>) {
-        egui::collapsing_header::CollapsingState::load_with_default_open(
+        let highlighted = editor.highlighted == Some(self.mod_ref().unwrap().id);
+
+        let (_, header, body) = egui::collapsing_header::CollapsingState::load_with_default_open(
             ui.ctx(),
             ui.make_persistent_id(self.mod_ref().unwrap().id),
             true,
@@ -112,14 +142,45 @@ impl ModifierSlot {
                     ui.label(text);
                 }
 
+                if let Some(tag) = modifier.label.tag {
+                    let (rect, _) = ui.allocate_exact_size(Vec2::new(6.0, 16.0), Sense::hover());
+                    ui.painter()
+                        .rect_filled(rect, Rounding::same(2.0), Color32::from(tag));
+                }
+
+                editor.thumbnails.show(ui, modifier);
+
+                let hint = [
+                    (!modifier.label.name.is_empty())
+                        .then_some(modifier.modifier.index.name.as_str()),
+                    (!modifier.label.note.is_empty()).then_some(modifier.label.note.as_str()),
+                    Some("ctrl click to select more, shift click for a range"),
+                ];
+
                 if ui
-                    .toggle_value(
-                        &mut (editor.selected_id() == Some(modifier.id)),
-                        &self.mod_ref().unwrap().modifier.index.name,
-                    )
+                    .toggle_value(&mut editor.is_selected(modifier.id), label::title(modifier))
+                    .on_hover_text(hint.into_iter().flatten().collect::<Vec<_>>().join("\n\n"))
                     .clicked()
                 {
-                    editor.select_cation(modifier);
+                    let modifiers = ui.input(|input| input.modifiers);
+                    if modifiers.command {
+                        editor.toggle_selected(modifier);
+                    } else if modifiers.shift {
+                        editor.select_range = Some(modifier.id);
+                    } else {
+                        editor.select_cation(modifier);
+                    }
+                }
+
+                if let Some(list) = modifier.modifier.modifier::<List>() {
+                    let names = list.iter_mods().map(label::title).collect::<Vec<_>>();
+                    ui.weak(format!("({})", names.len()))
+                        .on_hover_text(names.join("\n"));
+                }
+
+                if modifier.stats.recomputes > 0 {
+                    ui.weak(profiler::format_duration(modifier.stats.last))
+                        .on_hover_text(profiler::summary(modifier));
                 }
 
                 if ui
@@ -136,12 +197,40 @@ impl ModifierSlot {
                     }
                 });
             }
+
+            if let Some(modifier) = self.mod_mut() {
+                ui.toggle_value(&mut modifier.bypass, "⏸")
+                    .on_hover_text("bypass");
+                label::menu(&mut modifier.label, ui);
+                editor
+                    .presets
+                    .menu(ui, &mut modifier.modifier, &mut editor.notifications);
+            }
+
+            // only lists and graphs number the modifiers in them
+            clipboard::menu(self, ui, editor, prefix.is_some());
+
+            macros::header(self, ui, editor);
         })
         .body(|ui| {
             if let Some(modifier) = self.mod_mut() {
-                modifier.modifier.view(ui, editor)
+                let parent = editor.preview.parent.replace(modifier.id);
+                modifier.modifier.view(ui, editor);
+                editor.preview.parent = parent;
+                keyframes::view(modifier, ui, editor.time);
             }
         });
+
+        if highlighted {
+            let rect = body.map_or(header.response.rect, |body| {
+                header.response.rect.union(body.response.rect)
+            });
+            ui.painter().rect_stroke(
+                rect.expand(2.0),
+                Rounding::same(3.0),
+                ui.visuals().selection.stroke,
+            );
+        }
     }
 
     fn view_slot(&mut self, ui: &mut Ui, editor: &mut Editor) {
@@ -166,7 +255,11 @@ impl ModifierSlot {
 
     pub fn view(&mut self, ui: &mut Ui, editor: &mut Editor, prefix: Option<&str>) {
         match self {
-            Self::Modifier(_) => self.view_modifier(ui, editor, prefix),
+            Self::Modifier(cation) => {
+                if editor.filter.is_empty() || label::matches_nested(cation, &editor.filter) {
+                    self.view_modifier(ui, editor, prefix)
+                }
+            }
             Self::Empty => {
                 if editor.dragging.is_some() {
                     self.view_slot(ui, editor);
@@ -194,7 +287,14 @@ impl ModifierSlot {
         }
     }
 
-    pub fn view_with_frame(&mut self, ui: &mut Ui, editor: &mut Editor, prefix: Option<&str>) {
+    /// Shows the slot at the index among the slots of the modifier being drawn.
+    pub fn view_with_frame(
+        &mut self,
+        ui: &mut Ui,
+        editor: &mut Editor,
+        index: usize,
+        prefix: Option<&str>,
+    ) {
         Frame {
             inner_margin: Margin::same(3.0),
             rounding: Rounding::same(3.0),
@@ -205,7 +305,14 @@ impl ModifierSlot {
             if let Self::Empty = self {
                 if editor.dragging.is_none() {
                     ui.horizontal(|ui| {
-                        self.add_mod_widget(ui, editor);
+                        let target = editor
+                            .preview
+                            .parent
+                            .map(|parent| Target::Slot { parent, index });
+                        self.add_mod_widget(ui, editor, target);
+                        if let Some(pasted) = clipboard::paste_button(ui, editor) {
+                            *self = pasted;
+                        }
                         ui.centered_and_justified(|ui| {
                             ui.label("empty");
                         })
@@ -217,27 +324,53 @@ impl ModifierSlot {
         });
     }
 
-    pub fn add_mod_widget(&mut self, ui: &mut Ui, editor: &mut Editor) {
+    /// Menu to put a modifier in the slot. Hovering one previews it at the target, if there is
+    /// one.
+    pub fn add_mod_widget(&mut self, ui: &mut Ui, editor: &mut Editor, target: Option<Target>) {
         let mut text_edit_id = None;
 
         let inner = ui.menu_button("➕", |ui| {
             let response = ui.text_edit_singleline(&mut editor.add_mod_text);
             text_edit_id = Some(response.id);
+
+            let highlighted_id = response.id.with("highlighted");
+            let mut highlighted = ui.data(|data| data.get_temp(highlighted_id).unwrap_or(0));
+            if response.changed() {
+                highlighted = 0;
+            }
+
+            let (indices, items): (Vec<_>, Vec<_>) =
+                palette::modifier_items(&editor.add_mod_text, editor)
+                    .into_iter()
+                    .unzip();
+
             ui.separator();
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                for index in editor.index.iter().filter(|index| {
-                    editor.add_mod_text.is_empty()
-                        || index
-                            .name
-                            .to_lowercase()
-                            .contains(&editor.add_mod_text.to_lowercase())
-                }) {
-                    if ui.button(index.name.as_str()).clicked() {
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| match palette::list(ui, items, &mut highlighted) {
+                    Some(Event::Picked(i)) => {
                         ui.close_menu();
-                        *self = ModifierSlot::from_index(index);
+                        if let Err(err) = editor.usage.used(&indices[i].name) {
+                            editor
+                                .notifications
+                                .error(format!("failed to save modifier usage: {err}"));
+                        }
+                        *self = ModifierSlot::from_index(&indices[i]);
                     }
-                }
-            });
+                    Some(Event::Favourite(i)) => {
+                        if let Err(err) = editor.usage.toggle_favourite(&indices[i].name) {
+                            editor
+                                .notifications
+                                .error(format!("failed to save modifier usage: {err}"));
+                        }
+                    }
+                    Some(Event::Hovered(i)) => {
+                        editor.preview.hovered = target.map(|target| (indices[i].clone(), target));
+                    }
+                    None => (),
+                });
+
+            ui.data_mut(|data| data.insert_temp(highlighted_id, highlighted));
         });
         if inner.response.clicked() {
             if let Some(id) = text_edit_id {
@@ -0,0 +1,167 @@
+use std::{
+    collections::HashMap,
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+};
+
+use eframe::egui::{
+    Color32, ColorImage, Context, Pos2, Rect, Sense, TextureHandle, TextureOptions, Ui, Vec2,
+};
+use image::{imageops, RgbaImage};
+use uuid::Uuid;
+
+use crate::modifier::cation::{Cation, DynMod};
+
+const SIZE: u32 = 24;
+/// Thumbnails are filtered down from a sample this many times their size, picked on the ui
+/// thread so the full output is not copied.
+const SAMPLE: u32 = 4;
+
+/// Small previews of each slot's cached output, downscaled on a worker thread.
+pub struct Thumbnails {
+    entries: HashMap<Uuid, Entry>,
+    requests: Sender<Request>,
+    receiver: Receiver<Generated>,
+}
+
+struct Entry {
+    output_id: Uuid,
+    texture: Option<TextureHandle>,
+    used: bool,
+}
+
+struct Request {
+    id: Uuid,
+    output_id: Uuid,
+    sample: RgbaImage,
+    ctx: Context,
+}
+
+struct Generated {
+    id: Uuid,
+    output_id: Uuid,
+    image: ColorImage,
+}
+
+impl Default for Thumbnails {
+    fn default() -> Self {
+        let (requests, pending) = channel();
+        let (sender, receiver) = channel();
+        thread::spawn(move || work(pending, sender));
+
+        Self {
+            entries: HashMap::new(),
+            requests,
+            receiver,
+        }
+    }
+}
+
+impl Thumbnails {
+    /// Uploads finished thumbnails and forgets the ones that were not shown last frame.
+    pub fn update(&mut self, ctx: &Context) {
+        while let Ok(generated) = self.receiver.try_recv() {
+            if let Some(entry) = self.entries.get_mut(&generated.id) {
+                if entry.output_id == generated.output_id {
+                    entry.texture = Some(ctx.load_texture(
+                        format!("thumbnail-{}", generated.id),
+                        generated.image,
+                        TextureOptions::LINEAR,
+                    ));
+                }
+            }
+        }
+
+        self.entries
+            .retain(|_, entry| std::mem::take(&mut entry.used));
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, cation: &Cation<DynMod>) {
+        let (rect, _) = ui.allocate_exact_size(Vec2::splat(SIZE as f32), Sense::hover());
+
+        let Some(output) = cation.cached_output() else {
+            ui.painter()
+                .rect_filled(rect, 2.0, ui.visuals().widgets.inactive.bg_fill);
+            return;
+        };
+
+        let entry = self.entries.entry(cation.id).or_insert_with(|| Entry {
+            output_id: Uuid::nil(),
+            texture: None,
+            used: false,
+        });
+        entry.used = true;
+
+        if entry.output_id != output.id {
+            entry.output_id = output.id;
+
+            if let Some(image) = &output.image {
+                self.requests
+                    .send(Request {
+                        id: cation.id,
+                        output_id: output.id,
+                        sample: sample(image.as_rgba8()),
+                        ctx: ui.ctx().clone(),
+                    })
+                    .ok();
+            }
+        }
+
+        if let Some(texture) = &entry.texture {
+            ui.painter().image(
+                texture.id(),
+                Rect::from_center_size(rect.center(), texture.size_vec2()),
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                Color32::WHITE,
+            );
+        } else {
+            ui.painter()
+                .rect_filled(rect, 2.0, ui.visuals().widgets.inactive.bg_fill);
+        }
+    }
+}
+
+/// Picks the pixels nearest to a grid a few times the size of a thumbnail.
+fn sample(image: &RgbaImage) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let scale = (SIZE * SAMPLE) as f32 / width.max(height) as f32;
+    if scale >= 1.0 {
+        return image.clone();
+    }
+
+    let sampled_width = ((width as f32 * scale) as u32).max(1);
+    let sampled_height = ((height as f32 * scale) as u32).max(1);
+    RgbaImage::from_fn(sampled_width, sampled_height, |x, y| {
+        *image.get_pixel(x * width / sampled_width, y * height / sampled_height)
+    })
+}
+
+/// Downscales the requested samples until the thumbnails are dropped. Requests that arrive while
+/// it is busy are collected first, so only the latest output of each modifier is downscaled.
+fn work(requests: Receiver<Request>, generated: Sender<Generated>) {
+    while let Ok(request) = requests.recv() {
+        let mut latest = HashMap::from([(request.id, request)]);
+        latest.extend(requests.try_iter().map(|request| (request.id, request)));
+
+        for request in latest.into_values() {
+            let (width, height) = request.sample.dimensions();
+            let scale = SIZE as f32 / width.max(height) as f32;
+            let image = imageops::thumbnail(
+                &request.sample,
+                ((width as f32 * scale) as u32).max(1),
+                ((height as f32 * scale) as u32).max(1),
+            );
+            let size = [image.width() as usize, image.height() as usize];
+
+            let sent = generated.send(Generated {
+                id: request.id,
+                output_id: request.output_id,
+                image: ColorImage::from_rgba_unmultiplied(size, image.as_flat_samples().as_slice()),
+            });
+            if sent.is_err() {
+                return;
+            }
+            request.ctx.request_repaint();
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use eframe::egui::{self, Context};
+
+use crate::project::Project;
+
+/// Scrubbing and playback of the project's keyframes.
+#[derive(Default)]
+pub struct Timeline {
+    pub open: bool,
+    playing: bool,
+}
+
+impl Timeline {
+    pub fn view(&mut self, ctx: &Context, project: &mut Project) {
+        if !self.open {
+            self.playing = false;
+            return;
+        }
+
+        let duration = project.duration();
+        let mut time = project.time();
+
+        if self.playing {
+            time += ctx.input(|input| input.stable_dt);
+            if time > duration {
+                time = 0.0;
+            }
+            ctx.request_repaint();
+        }
+
+        egui::TopBottomPanel::bottom("timeline").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button(if self.playing { "⏸" } else { "▶" }).clicked() {
+                    self.playing = !self.playing;
+                }
+
+                ui.spacing_mut().slider_width = ui.available_width() - 80.0;
+                ui.add(
+                    egui::Slider::new(&mut time, 0.0..=duration.max(1.0))
+                        .suffix("s")
+                        .max_decimals(2),
+                );
+            });
+        });
+
+        if time != project.time() {
+            project.set_time(time);
+        }
+    }
+}
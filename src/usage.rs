@@ -0,0 +1,74 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// How many recently added modifiers are remembered.
+const RECENT_LIMIT: usize = 5;
+
+/// The modifiers the user added last and marked as favourite, by name, kept in the config
+/// directory.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    favourites: Vec<String>,
+    #[serde(default)]
+    recent: Vec<String>,
+}
+
+impl Usage {
+    pub fn user_path() -> Option<PathBuf> {
+        Some(
+            dirs::config_dir()?
+                .join(env!("CARGO_PKG_NAME"))
+                .join("usage.json"),
+        )
+    }
+
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::user_path().filter(|path| path.exists()) else {
+            return Ok(Self::default());
+        };
+
+        let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        serde_json::from_str(&text).map_err(|err| err.to_string())
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = Self::user_path().ok_or("no config directory")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(|err| err.to_string())?;
+        fs::write(path, text).map_err(|err| err.to_string())
+    }
+
+    pub fn favourites(&self) -> &[String] {
+        &self.favourites
+    }
+
+    pub fn recent(&self) -> &[String] {
+        &self.recent
+    }
+
+    pub fn is_favourite(&self, name: &str) -> bool {
+        self.favourites.iter().any(|favourite| favourite == name)
+    }
+
+    pub fn toggle_favourite(&mut self, name: &str) -> Result<(), String> {
+        if self.is_favourite(name) {
+            self.favourites.retain(|favourite| favourite != name);
+        } else {
+            self.favourites.push(name.to_string());
+            self.favourites.sort();
+        }
+        self.save()
+    }
+
+    /// Moves the modifier to the top of the recently added ones.
+    pub fn used(&mut self, name: &str) -> Result<(), String> {
+        self.recent.retain(|recent| recent != name);
+        self.recent.insert(0, name.to_string());
+        self.recent.truncate(RECENT_LIMIT);
+        self.save()
+    }
+}
@@ -0,0 +1,630 @@
+use eframe::egui::{
+    self, Align2, Color32, ColorImage, Context, CursorIcon, FontId, Frame, LayerId, Order, Pos2,
+    Rect, Sense, Stroke, Ui, Vec2,
+};
+
+use self::tiles::TiledTexture;
+use crate::{
+    guides::{Guide, Guides},
+    image::Image,
+    keybinds,
+    position::Position,
+};
+
+mod tiles;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Background {
+    Checkerboard,
+    Solid(Color32),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Display {
+    Color,
+    AlphaOnly,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Checkerboard {
+    pub colors: [Color32; 2],
+    pub size: f32,
+}
+
+impl Default for Checkerboard {
+    fn default() -> Self {
+        Self {
+            colors: [Color32::from_gray(204), Color32::from_gray(153)],
+            size: 8.0,
+        }
+    }
+}
+
+pub struct View {
+    tiles: TiledTexture,
+    pub scale: f32,
+    pub translation: Vec2,
+    dragging: bool,
+    pub rect: Rect,
+    canvas: Rect,
+    pub show_grid: bool,
+    pub show_rulers: bool,
+    /// The project's guides, moved here while the modifiers are drawn for them to snap to.
+    pub guides: Guides,
+    dragged_guide: Option<Guide>,
+    pub show_navigator: bool,
+    navigator: Rect,
+    /// Fits the image once the one with this number is shown, the first requested after the
+    /// fit was.
+    fit_after: Option<u64>,
+    pub background: Background,
+    pub checkerboard: Checkerboard,
+    display: Display,
+    refresh: bool,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self {
+            tiles: TiledTexture::default(),
+            scale: 1.0,
+            translation: Vec2::ZERO,
+            dragging: false,
+            rect: Rect::NAN,
+            canvas: Rect::NAN,
+            show_grid: true,
+            show_rulers: true,
+            guides: Guides::default(),
+            dragged_guide: None,
+            show_navigator: true,
+            navigator: Rect::NOTHING,
+            fit_after: Some(1),
+            background: Background::Checkerboard,
+            checkerboard: Checkerboard::default(),
+            display: Display::Color,
+            refresh: false,
+        }
+    }
+}
+
+impl View {
+    const RULER: f32 = 18.0;
+    const GRID_THRESHOLD: f32 = 8.0;
+    const SNAP_DISTANCE: f32 = 6.0;
+    const NAVIGATOR: Vec2 = Vec2::new(160.0, 120.0);
+
+    /// Shows the image once it is converted and uploaded, which happens on another thread.
+    pub fn update(&mut self, ctx: &Context, image: &Image) {
+        let image = image.clone();
+        let display = self.display;
+
+        self.refresh = false;
+
+        self.tiles.update(ctx, move || {
+            let image = image.as_rgba8();
+            let size = [image.width() as usize, image.height() as usize];
+            let pixels = image.as_flat_samples();
+
+            match display {
+                Display::Color => ColorImage::from_rgba_unmultiplied(size, pixels.as_slice()),
+                Display::AlphaOnly => ColorImage::from_gray(
+                    size,
+                    &pixels
+                        .as_slice()
+                        .iter()
+                        .skip(3)
+                        .step_by(4)
+                        .copied()
+                        .collect::<Vec<u8>>(),
+                ),
+            }
+        });
+    }
+
+    pub fn process(&mut self, ctx: &Context, guides: &mut Guides) {
+        self.tiles.poll(ctx);
+
+        egui::CentralPanel::default()
+            .frame(Frame::central_panel(&ctx.style()).inner_margin(0.0))
+            .show(ctx, |ui| {
+                self.canvas = if self.show_rulers {
+                    Rect::from_min_max(
+                        ui.max_rect().min + Vec2::splat(Self::RULER),
+                        ui.max_rect().max,
+                    )
+                } else {
+                    ui.max_rect()
+                };
+
+                if !self.tiles.is_empty() {
+                    if self
+                        .fit_after
+                        .is_some_and(|number| self.tiles.has_shown(number))
+                    {
+                        self.fit(ctx);
+                        self.fit_after = None;
+                    }
+
+                    self.input_navigator(ui);
+                    self.input_zoom(ui);
+                    self.input_drag(ui);
+                    self.view(ui);
+
+                    if self.show_grid {
+                        self.view_grid(ui);
+                    }
+
+                    self.input_guides(ui, guides);
+                    self.view_guides(ui, guides);
+
+                    if self.show_rulers {
+                        self.view_rulers(ui);
+                    }
+
+                    if self.show_navigator {
+                        self.view_navigator(ui);
+                    }
+
+                    if keybinds::loupe(ctx) {
+                        self.view_loupe(ui);
+                    }
+                }
+            });
+    }
+
+    fn input_zoom(&mut self, ui: &mut Ui) {
+        const FACTOR: f32 = 1.3;
+
+        if ui.rect_contains_pointer(ui.max_rect()) {
+            let pointer = ui.input(|input| input.pointer.interact_pos().unwrap());
+            let center_pointer = pointer.to_vec2() - self.rect.center().to_vec2();
+
+            let scrolled = ui.ctx().input(|input| input.scroll_delta.y);
+            if scrolled.is_normal() {
+                if scrolled.is_sign_positive() {
+                    let diff = center_pointer * FACTOR - center_pointer;
+                    self.translation -= diff;
+                    self.scale *= FACTOR;
+                } else {
+                    let diff = center_pointer / FACTOR - center_pointer;
+                    self.translation -= diff;
+                    self.scale /= FACTOR;
+                }
+            }
+        }
+    }
+
+    fn input_drag(&mut self, ui: &mut Ui) {
+        let pan = keybinds::pan(ui.ctx());
+        if pan && ui.rect_contains_pointer(self.canvas) {
+            ui.ctx().set_cursor_icon(if self.dragging {
+                CursorIcon::Grabbing
+            } else {
+                CursorIcon::Grab
+            });
+        }
+
+        if ui.rect_contains_pointer(ui.max_rect()) {
+            if ui.ctx().input(|input| {
+                input.pointer.any_pressed()
+                    && (input.pointer.middle_down() || (pan && input.pointer.primary_down()))
+            }) {
+                self.dragging = true;
+            } else if ui.ctx().input(|input| input.pointer.any_released()) {
+                self.dragging = false;
+            }
+        }
+
+        if self.dragging && ui.ctx().input(|input| input.pointer.any_down()) {
+            self.translation += ui.ctx().input(|input| input.pointer.delta());
+        } else {
+            self.dragging = false;
+        }
+    }
+
+    fn view(&mut self, ui: &mut Ui) {
+        let size = self.tiles.size() / ui.ctx().pixels_per_point() * self.scale;
+        let center = self.canvas.center() + self.translation;
+        self.rect = Rect::from_center_size(center, size);
+
+        if self.display == Display::Color {
+            self.view_background(ui);
+        }
+
+        self.tiles.paint(
+            &ui.painter().with_clip_rect(ui.max_rect()),
+            self.rect,
+            ui.ctx().pixels_per_point(),
+        );
+    }
+
+    fn view_background(&self, ui: &mut Ui) {
+        let visible = self.rect.intersect(self.canvas);
+        let painter = ui.painter().with_clip_rect(visible);
+
+        match self.background {
+            Background::Solid(color) => {
+                painter.rect_filled(visible, 0.0, color);
+            }
+            Background::Checkerboard => {
+                let Checkerboard { colors, size } = self.checkerboard;
+                painter.rect_filled(visible, 0.0, colors[0]);
+
+                let min = ((visible.min - self.rect.min) / size).floor();
+                let max = ((visible.max - self.rect.min) / size).ceil();
+
+                for y in min.y as i32..max.y as i32 {
+                    for x in (min.x as i32..max.x as i32).filter(|x| (x + y) % 2 != 0) {
+                        let cell = self.rect.min + Vec2::new(x as f32, y as f32) * size;
+                        painter.rect_filled(
+                            Rect::from_min_size(cell, Vec2::splat(size)),
+                            0.0,
+                            colors[1],
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn display(&self) -> Display {
+        self.display
+    }
+
+    pub fn set_display(&mut self, display: Display) {
+        if self.display != display {
+            self.display = display;
+            self.refresh = true;
+        }
+    }
+
+    /// Whether the texture has to be uploaded again even though the output did not change.
+    pub fn needs_refresh(&self) -> bool {
+        self.refresh
+    }
+
+    pub fn request_refresh(&mut self) {
+        self.refresh = true;
+    }
+
+    /// Size of the whole image on screen at 100%, in points.
+    fn image_size(&self, ctx: &Context) -> Vec2 {
+        self.tiles.size() / ctx.pixels_per_point()
+    }
+
+    pub fn request_fit(&mut self) {
+        self.fit_after = Some(self.tiles.requested() + 1);
+    }
+
+    pub fn fit(&mut self, ctx: &Context) {
+        let ratio = self.canvas.size() / self.image_size(ctx);
+        if ratio.min_elem().is_normal() {
+            self.scale = ratio.min_elem();
+            self.translation = Vec2::ZERO;
+        }
+    }
+
+    pub fn fill(&mut self, ctx: &Context) {
+        let ratio = self.canvas.size() / self.image_size(ctx);
+        if ratio.max_elem().is_normal() {
+            self.scale = ratio.max_elem();
+            self.translation = Vec2::ZERO;
+        }
+    }
+
+    pub fn actual_size(&mut self) {
+        self.scale = 1.0;
+        self.translation = Vec2::ZERO;
+    }
+
+    /// Zooms around the center of the canvas.
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.scale *= factor;
+        self.translation *= factor;
+    }
+
+    /// Where the navigator thumbnail and its image are drawn, fitted to the image's aspect ratio.
+    fn navigator_rect(&self) -> Rect {
+        let ratio = Self::NAVIGATOR / self.tiles.size();
+        let size = self.tiles.size() * ratio.min_elem();
+
+        Rect::from_min_size(self.canvas.max - size - Vec2::splat(8.0), size)
+    }
+
+    fn input_navigator(&mut self, ui: &mut Ui) {
+        if !self.show_navigator {
+            self.navigator = Rect::NOTHING;
+            return;
+        }
+
+        self.navigator = self.navigator_rect();
+
+        let response = ui.interact(self.navigator, ui.id().with("navigator"), Sense::drag());
+        if response.dragged() || response.clicked() {
+            if let Some(pointer) = response.interact_pointer_pos() {
+                let relative = (pointer - self.navigator.min) / self.navigator.size();
+                let size = self.rect.size();
+                self.translation = size / 2.0 - relative * size;
+            }
+        }
+    }
+
+    fn view_navigator(&self, ui: &mut Ui) {
+        let painter = ui.painter();
+        let visuals = ui.visuals();
+
+        painter.rect_filled(self.navigator.expand(2.0), 2.0, visuals.extreme_bg_color);
+        self.tiles
+            .paint(painter, self.navigator, ui.ctx().pixels_per_point());
+
+        let to_navigator = |pos: Pos2| {
+            self.navigator.min + (pos - self.rect.min) / self.rect.size() * self.navigator.size()
+        };
+        let visible =
+            Rect::from_min_max(to_navigator(self.canvas.min), to_navigator(self.canvas.max))
+                .intersect(self.navigator);
+
+        painter
+            .with_clip_rect(self.navigator.expand(1.0))
+            .rect_stroke(visible, 0.0, visuals.selection.stroke);
+        painter.rect_stroke(self.navigator.expand(2.0), 2.0, visuals.window_stroke());
+    }
+
+    /// Size of one image pixel on screen, in points.
+    fn pixel_size(&self) -> f32 {
+        self.rect.width() / self.tiles.size().x
+    }
+
+    fn screen_to_pixel(&self, pos: Pos2) -> Vec2 {
+        (pos - self.rect.min) / self.pixel_size()
+    }
+
+    fn pixel_to_screen(&self, pixel: Vec2) -> Pos2 {
+        self.rect.min + pixel * self.pixel_size()
+    }
+
+    fn view_grid(&self, ui: &mut Ui) {
+        let pixel = self.pixel_size();
+        if pixel < Self::GRID_THRESHOLD {
+            return;
+        }
+
+        let visible = self.rect.intersect(self.canvas);
+        let painter = ui.painter().with_clip_rect(visible);
+        let stroke = Stroke::new(1.0_f32, Color32::from_rgba_unmultiplied(128, 128, 128, 96));
+
+        let min = self.screen_to_pixel(visible.min).floor();
+        let max = self.screen_to_pixel(visible.max).ceil();
+
+        for x in min.x as i32..=max.x as i32 {
+            let x = self.pixel_to_screen(Vec2::new(x as f32, 0.0)).x;
+            painter.vline(x, visible.y_range(), stroke);
+        }
+
+        for y in min.y as i32..=max.y as i32 {
+            let y = self.pixel_to_screen(Vec2::new(0.0, y as f32)).y;
+            painter.hline(visible.x_range(), y, stroke);
+        }
+    }
+
+    fn rulers(&self, ui: &Ui) -> (Rect, Rect) {
+        let max_rect = ui.max_rect();
+        (
+            Rect::from_min_max(
+                Pos2::new(self.canvas.min.x, max_rect.min.y),
+                Pos2::new(max_rect.max.x, self.canvas.min.y),
+            ),
+            Rect::from_min_max(
+                Pos2::new(max_rect.min.x, self.canvas.min.y),
+                Pos2::new(self.canvas.min.x, max_rect.max.y),
+            ),
+        )
+    }
+
+    fn view_rulers(&self, ui: &mut Ui) {
+        let (top, left) = self.rulers(ui);
+        let painter = ui.painter();
+        let visuals = ui.visuals();
+
+        painter.rect_filled(
+            Rect::from_min_max(ui.max_rect().min, self.canvas.min),
+            0.0,
+            visuals.panel_fill,
+        );
+        painter.rect_filled(top, 0.0, visuals.panel_fill);
+        painter.rect_filled(left, 0.0, visuals.panel_fill);
+
+        let step = ruler_step(self.pixel_size());
+        let stroke = Stroke::new(1.0_f32, visuals.text_color());
+        let font = FontId::monospace(9.0);
+
+        let top_painter = painter.with_clip_rect(top);
+        let min = (self.screen_to_pixel(top.min).x / step).floor() as i32;
+        let max = (self.screen_to_pixel(top.max).x / step).ceil() as i32;
+        for i in min..=max {
+            let value = i as f32 * step;
+            let x = self.pixel_to_screen(Vec2::new(value, 0.0)).x;
+            top_painter.vline(x, (top.max.y - Self::RULER / 2.0)..=top.max.y, stroke);
+            top_painter.text(
+                Pos2::new(x + 2.0, top.min.y),
+                Align2::LEFT_TOP,
+                value,
+                font.clone(),
+                visuals.text_color(),
+            );
+        }
+
+        let left_painter = painter.with_clip_rect(left);
+        let min = (self.screen_to_pixel(left.min).y / step).floor() as i32;
+        let max = (self.screen_to_pixel(left.max).y / step).ceil() as i32;
+        for i in min..=max {
+            let value = i as f32 * step;
+            let y = self.pixel_to_screen(Vec2::new(0.0, value)).y;
+            left_painter.hline((left.max.x - Self::RULER / 2.0)..=left.max.x, y, stroke);
+            left_painter.text(
+                Pos2::new(left.min.x, y + 2.0),
+                Align2::LEFT_TOP,
+                value,
+                font.clone(),
+                visuals.text_color(),
+            );
+        }
+
+        painter.hline(
+            ui.max_rect().x_range(),
+            self.canvas.min.y,
+            visuals.window_stroke(),
+        );
+        painter.vline(
+            self.canvas.min.x,
+            ui.max_rect().y_range(),
+            visuals.window_stroke(),
+        );
+    }
+
+    fn input_guides(&mut self, ui: &mut Ui, guides: &mut Guides) {
+        let Some(pointer) = ui.ctx().pointer_interact_pos() else {
+            return;
+        };
+
+        let pixel = self.screen_to_pixel(pointer).round();
+        if let Some(guide) = &mut self.dragged_guide {
+            match guide {
+                Guide::Horizontal(y) => *y = pixel.y,
+                Guide::Vertical(x) => *x = pixel.x,
+            }
+
+            if !ui.input(|input| input.pointer.primary_down()) {
+                let guide = self.dragged_guide.take().unwrap();
+                if self.canvas.contains(pointer) {
+                    guides.lines.push(guide);
+                }
+            }
+
+            return;
+        }
+
+        if self.show_rulers {
+            let (top, left) = self.rulers(ui);
+            if ui
+                .interact(top, ui.id().with("ruler_top"), Sense::drag())
+                .drag_started()
+            {
+                self.dragged_guide = Some(Guide::Horizontal(0.0));
+            } else if ui
+                .interact(left, ui.id().with("ruler_left"), Sense::drag())
+                .drag_started()
+            {
+                self.dragged_guide = Some(Guide::Vertical(0.0));
+            }
+        }
+
+        let grabbed = guides.lines.iter().enumerate().position(|(index, guide)| {
+            let rect = self.guide_rect(*guide).expand(3.0);
+
+            ui.interact(rect, ui.id().with("guide").with(index), Sense::drag())
+                .on_hover_cursor(match guide {
+                    Guide::Horizontal(_) => CursorIcon::ResizeVertical,
+                    Guide::Vertical(_) => CursorIcon::ResizeHorizontal,
+                })
+                .drag_started()
+        });
+
+        if let Some(index) = grabbed {
+            self.dragged_guide = Some(guides.lines.remove(index));
+        }
+    }
+
+    fn guide_rect(&self, guide: Guide) -> Rect {
+        match guide {
+            Guide::Horizontal(y) => {
+                let y = self.pixel_to_screen(Vec2::new(0.0, y)).y;
+                Rect::from_x_y_ranges(self.canvas.x_range(), y..=y)
+            }
+            Guide::Vertical(x) => {
+                let x = self.pixel_to_screen(Vec2::new(x, 0.0)).x;
+                Rect::from_x_y_ranges(x..=x, self.canvas.y_range())
+            }
+        }
+    }
+
+    fn view_guides(&self, ui: &mut Ui, guides: &Guides) {
+        let painter = ui.painter().with_clip_rect(self.canvas);
+        let stroke = Stroke::new(1.0_f32, Color32::from_rgb(0, 200, 255));
+
+        for guide in guides.lines.iter().chain(self.dragged_guide.iter()) {
+            let rect = self.guide_rect(*guide);
+            painter.line_segment([rect.min, rect.max], stroke);
+        }
+    }
+
+    /// Snaps the top left corner of a rectangle of the given size, both in pixels, to the guides.
+    pub fn snap_rect(&self, min: Position, size: Position) -> Position {
+        self.guides
+            .snap_rect(min, size, Self::SNAP_DISTANCE / self.pixel_size())
+    }
+
+    fn view_loupe(&self, ui: &mut Ui) {
+        const RADIUS: f32 = 7.0;
+        const SIZE: f32 = 150.0;
+
+        let (Some(pixel), Some(pointer)) = (
+            self.hovered_pixel(ui.ctx()),
+            ui.ctx().pointer_interact_pos(),
+        ) else {
+            return;
+        };
+
+        let cell = SIZE / (RADIUS * 2.0 + 1.0);
+        let center = Pos2::new(pixel.x.floor(), pixel.y.floor());
+        let region = Rect::from_min_max(
+            center - Vec2::splat(RADIUS),
+            center + Vec2::splat(RADIUS + 1.0),
+        );
+        let loupe = Rect::from_min_size(pointer + Vec2::splat(20.0), Vec2::splat(SIZE));
+        let painter = ui
+            .ctx()
+            .layer_painter(LayerId::new(Order::Tooltip, ui.id().with("loupe")));
+
+        painter.rect_filled(loupe, 0.0, ui.visuals().extreme_bg_color);
+        self.tiles.paint_level(
+            &painter.with_clip_rect(loupe),
+            Rect::from_min_size(
+                loupe.min - region.min.to_vec2() * cell,
+                self.tiles.size() * cell,
+            ),
+            0,
+        );
+        painter.rect_stroke(
+            Rect::from_min_size(loupe.min + Vec2::splat(RADIUS * cell), Vec2::splat(cell)),
+            0.0,
+            Stroke::new(1.0_f32, Color32::WHITE),
+        );
+        painter.rect_stroke(loupe, 0.0, ui.visuals().window_stroke());
+    }
+
+    pub fn hovered_pixel(&self, ctx: &Context) -> Option<Vec2> {
+        let pointer = ctx.input(|input| input.pointer.interact_pos())?;
+        if self.rect.contains(pointer)
+            && self.canvas.contains(pointer)
+            && !self.navigator.contains(pointer)
+            && !keybinds::pan(ctx)
+        {
+            let pos = pointer - self.rect.left_top();
+            Some(pos / self.scale * ctx.pixels_per_point())
+        } else {
+            None
+        }
+    }
+}
+
+/// Picks a distance between ruler ticks, in pixels, that keeps labels readable at this zoom.
+fn ruler_step(pixel_size: f32) -> f32 {
+    const MIN_SPACING: f32 = 50.0;
+
+    (0..8)
+        .map(|exponent| 10f32.powi(exponent))
+        .flat_map(|magnitude| [1.0, 2.0, 5.0].map(|step| step * magnitude))
+        .find(|step| step * pixel_size >= MIN_SPACING)
+        .unwrap_or(1e8)
+}
@@ -0,0 +1,370 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+};
+
+use eframe::egui::{
+    Color32, ColorImage, Context, Painter, Pos2, Rect, TextureFilter, TextureHandle,
+    TextureOptions, Vec2,
+};
+
+const OPTIONS: TextureOptions = TextureOptions {
+    magnification: TextureFilter::Nearest,
+    minification: TextureFilter::Linear,
+};
+
+/// An image split over several textures so it can exceed the maximum texture size, with
+/// halved mip levels for drawing it zoomed out. The levels are built and compared on a worker
+/// thread, which only sends back the tiles that changed.
+pub struct TiledTexture {
+    levels: Vec<Level>,
+    requests: Sender<Request>,
+    receiver: Receiver<Prepared>,
+    /// The number of the last requested image and of the last one uploaded.
+    requested: u64,
+    uploaded: u64,
+}
+
+struct Level {
+    size: [usize; 2],
+    tiles: Vec<Tile>,
+}
+
+struct Tile {
+    min: [usize; 2],
+    size: [usize; 2],
+    texture: TextureHandle,
+}
+
+struct Request {
+    number: u64,
+    image: Box<dyn FnOnce() -> ColorImage + Send>,
+    tile_size: usize,
+    ctx: Context,
+}
+
+/// The levels of an image as tiles, with the pixels of only those that changed.
+struct Prepared {
+    number: u64,
+    levels: Vec<PreparedLevel>,
+}
+
+struct PreparedLevel {
+    size: [usize; 2],
+    tiles: Vec<PreparedTile>,
+}
+
+struct PreparedTile {
+    min: [usize; 2],
+    size: [usize; 2],
+    changed: Option<ColorImage>,
+}
+
+impl Default for TiledTexture {
+    fn default() -> Self {
+        let (requests, pending) = channel();
+        let (sender, receiver) = channel();
+        thread::spawn(move || work(pending, sender));
+
+        Self {
+            levels: Vec::new(),
+            requests,
+            receiver,
+            requested: 0,
+            uploaded: 0,
+        }
+    }
+}
+
+impl TiledTexture {
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// The number of the last requested image.
+    pub fn requested(&self) -> u64 {
+        self.requested
+    }
+
+    /// Whether the image with the given number, or a later one, is shown.
+    pub fn has_shown(&self, number: u64) -> bool {
+        self.uploaded >= number
+    }
+
+    /// Size of the full resolution image in pixels.
+    pub fn size(&self) -> Vec2 {
+        self.levels
+            .first()
+            .map_or(Vec2::ZERO, |level| level.size_vec2())
+    }
+
+    /// Builds the image on the worker thread and uploads it once it is done. Images requested
+    /// while the worker is busy replace each other.
+    pub fn update(&mut self, ctx: &Context, image: impl FnOnce() -> ColorImage + Send + 'static) {
+        self.requested += 1;
+        self.requests
+            .send(Request {
+                number: self.requested,
+                image: Box::new(image),
+                tile_size: ctx.input(|input| input.max_texture_side).min(2048),
+                ctx: ctx.clone(),
+            })
+            .ok();
+    }
+
+    /// Uploads the tiles that changed in the images built since the last call.
+    pub fn poll(&mut self, ctx: &Context) {
+        while let Ok(prepared) = self.receiver.try_recv() {
+            self.uploaded = prepared.number;
+            self.levels.truncate(prepared.levels.len());
+
+            for (index, prepared) in prepared.levels.into_iter().enumerate() {
+                if let Some(level) = self.levels.get_mut(index) {
+                    level.upload(ctx, prepared, index);
+                } else {
+                    let mut level = Level {
+                        size: prepared.size,
+                        tiles: Vec::new(),
+                    };
+                    level.upload(ctx, prepared, index);
+                    self.levels.push(level);
+                }
+            }
+        }
+    }
+
+    /// Paints the level whose resolution best matches the screen, with `rect` being where the
+    /// whole image ends up.
+    pub fn paint(&self, painter: &Painter, rect: Rect, pixels_per_point: f32) {
+        let density = rect.width() * pixels_per_point / self.size().x;
+
+        let level = (0..self.levels.len())
+            .rev()
+            .find(|level| density * self.downscale(*level) <= 1.0)
+            .unwrap_or(0);
+
+        self.paint_level(painter, rect, level);
+    }
+
+    pub fn paint_level(&self, painter: &Painter, rect: Rect, level: usize) {
+        let Some(level) = self.levels.get(level) else {
+            return;
+        };
+
+        let scale = rect.size() / level.size_vec2();
+
+        for tile in &level.tiles {
+            let min = rect.min + Vec2::new(tile.min[0] as f32, tile.min[1] as f32) * scale;
+            let size = Vec2::new(tile.size[0] as f32, tile.size[1] as f32) * scale;
+            let tile_rect = Rect::from_min_size(min, size);
+
+            if painter.clip_rect().intersects(tile_rect) {
+                painter.image(
+                    tile.texture.id(),
+                    tile_rect,
+                    Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                    Color32::WHITE,
+                );
+            }
+        }
+    }
+
+    fn downscale(&self, level: usize) -> f32 {
+        self.size().x / self.levels[level].size_vec2().x
+    }
+}
+
+impl Level {
+    fn size_vec2(&self) -> Vec2 {
+        Vec2::new(self.size[0] as f32, self.size[1] as f32)
+    }
+
+    /// Uploads the changed tiles. The worker sends every tile of a level that changed size.
+    fn upload(&mut self, ctx: &Context, prepared: PreparedLevel, index: usize) {
+        if self.size != prepared.size || self.tiles.len() != prepared.tiles.len() {
+            self.size = prepared.size;
+            self.tiles.clear();
+        }
+
+        for (tile_index, prepared) in prepared.tiles.into_iter().enumerate() {
+            match (self.tiles.get_mut(tile_index), prepared.changed) {
+                (Some(tile), changed) => {
+                    tile.min = prepared.min;
+                    tile.size = prepared.size;
+                    if let Some(image) = changed {
+                        tile.texture.set(image, OPTIONS);
+                    }
+                }
+                (None, Some(image)) => self.tiles.push(Tile {
+                    min: prepared.min,
+                    size: prepared.size,
+                    texture: ctx.load_texture(
+                        format!("image-{index}-{tile_index}"),
+                        image,
+                        OPTIONS,
+                    ),
+                }),
+                (None, None) => return,
+            }
+        }
+    }
+}
+
+/// Builds the latest requested image until the texture is dropped, keeping the hashes of the
+/// tiles it sent to leave out the ones that did not change.
+fn work(requests: Receiver<Request>, prepared: Sender<Prepared>) {
+    let mut hashes: Vec<([usize; 2], Vec<u64>)> = Vec::new();
+
+    let mut tile_size = 0;
+
+    while let Ok(request) = requests.recv() {
+        let request = requests.try_iter().last().unwrap_or(request);
+        if request.tile_size != tile_size {
+            tile_size = request.tile_size;
+            hashes.clear();
+        }
+
+        let mut images = vec![(request.image)()];
+        while images.last().unwrap().size.into_iter().max().unwrap() > 1 {
+            images.push(downsample(images.last().unwrap()));
+        }
+
+        hashes.truncate(images.len());
+
+        let levels = images
+            .iter()
+            .enumerate()
+            .map(|(index, image)| {
+                if hashes.get(index).map(|(size, _)| *size) != Some(image.size) {
+                    hashes.truncate(index);
+                    hashes.push((image.size, Vec::new()));
+                }
+
+                prepare(image, tile_size, &mut hashes[index].1)
+            })
+            .collect();
+
+        let sent = prepared.send(Prepared {
+            number: request.number,
+            levels,
+        });
+        if sent.is_err() {
+            return;
+        }
+        request.ctx.request_repaint();
+    }
+}
+
+/// Splits a level into tiles, with the pixels of those whose hash differs from the previous one.
+fn prepare(image: &ColorImage, tile_size: usize, hashes: &mut Vec<u64>) -> PreparedLevel {
+    let columns = image.size[0].div_ceil(tile_size);
+    let rows = image.size[1].div_ceil(tile_size);
+
+    if hashes.len() != columns * rows {
+        hashes.clear();
+    }
+
+    let mut tiles = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            let min = [column * tile_size, row * tile_size];
+            let size = [
+                tile_size.min(image.size[0] - min[0]),
+                tile_size.min(image.size[1] - min[1]),
+            ];
+
+            let region = region(image, min, size);
+            let mut hasher = DefaultHasher::new();
+            region.pixels.hash(&mut hasher);
+            let hash = hasher.finish();
+
+            let tile_index = row * columns + column;
+            let changed = if let Some(previous) = hashes.get_mut(tile_index) {
+                (*previous != hash).then(|| {
+                    *previous = hash;
+                    region
+                })
+            } else {
+                hashes.push(hash);
+                Some(region)
+            };
+
+            tiles.push(PreparedTile { min, size, changed });
+        }
+    }
+
+    PreparedLevel {
+        size: image.size,
+        tiles,
+    }
+}
+
+fn region(image: &ColorImage, min: [usize; 2], size: [usize; 2]) -> ColorImage {
+    let pixels = (min[1]..min[1] + size[1])
+        .flat_map(|y| {
+            let start = y * image.size[0] + min[0];
+            image.pixels[start..start + size[0]].iter().copied()
+        })
+        .collect();
+
+    ColorImage { size, pixels }
+}
+
+/// Halves the image by averaging each 2x2 block of (premultiplied) pixels.
+fn downsample(image: &ColorImage) -> ColorImage {
+    let [width, height] = image.size;
+    let size = [width.div_ceil(2).max(1), height.div_ceil(2).max(1)];
+
+    let pixels = (0..size[1])
+        .flat_map(|y| (0..size[0]).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let mut sum = [0u32; 4];
+            let mut count = 0;
+
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let (sx, sy) = (x * 2 + dx, y * 2 + dy);
+                if sx < width && sy < height {
+                    for (sum, value) in sum.iter_mut().zip(image[(sx, sy)].to_array()) {
+                        *sum += value as u32;
+                    }
+                    count += 1;
+                }
+            }
+
+            let [r, g, b, a] = sum.map(|sum| (sum / count) as u8);
+            Color32::from_rgba_premultiplied(r, g, b, a)
+        })
+        .collect();
+
+    ColorImage { size, pixels }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed(level: &PreparedLevel) -> usize {
+        level
+            .tiles
+            .iter()
+            .filter(|tile| tile.changed.is_some())
+            .count()
+    }
+
+    #[test]
+    fn only_changed_tiles_are_sent() {
+        let mut image = ColorImage::new([5, 3], Color32::BLACK);
+        let mut hashes = Vec::new();
+
+        assert_eq!(changed(&prepare(&image, 2, &mut hashes)), 6);
+        assert_eq!(changed(&prepare(&image, 2, &mut hashes)), 0);
+
+        image[(4, 2)] = Color32::WHITE;
+        let level = prepare(&image, 2, &mut hashes);
+        assert_eq!(changed(&level), 1);
+        assert_eq!(level.tiles[5].size, [1, 1]);
+        assert!(level.tiles[5].changed.is_some());
+    }
+}